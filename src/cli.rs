@@ -1,20 +1,40 @@
-use crate::commands;
+use crate::{
+    commands,
+    convert::{BufferFormat, Formats, ORG_CONVERTERS},
+    forges::{ForgeKind, FORGE_KINDS, MERGE_REQUEST_STATES},
+    output::{OutputFormat, OUTPUT_FORMATS},
+    settings::Settings,
+};
 use clap::{App, AppSettings, Arg, ArgMatches};
-use eyre::{Context, Report};
+use eyre::{eyre, Context, Report};
 
 const HELP: &str = r#"
 CLI for editing and creating issues and merge requests on Gitlab, Github, et al.
 
-The most prominent commands of this tool allow you to edit and create MRs and issues in your local text editor. They also offer you to edit and create MRs and issues in ORG mode instead of markdown by automatically converting your input before upload.
+The most prominent commands of this tool allow you to edit and create MRs and issues in your local text editor. They also offer you to edit and create MRs and issues in ORG mode instead of markdown by automatically converting your input before upload. The conversion is built in; pandoc can optionally be used instead via --converter. Other formats such as AsciiDoc, reStructuredText or Djot can be chosen via --format and converted by pandoc or by commands configured in the config file.
 
-Most parameters can be passed as environment variables instead of CLI arguments, allowing you to store these values locally per project in an .envrc/.env file (As long as you're not pushing FORGE_CLI_TOKEN, you're fine).
+Most parameters can be passed as environment variables instead of CLI arguments, allowing you to store these values locally per project in an .envrc/.env file (As long as you're not pushing FORGE_CLI_TOKEN, you're fine). Better yet, let the token be read from a password manager, a git credential helper or ~/.netrc as configured in the config file.
+
+Settings can also be stored in ~/.config/forge/config.toml and in a repository-local .forge.toml, optionally per host in a [hosts."<host name>"] section. Use `forge config show` to print the effective settings and where they came from.
 "#;
 
-const ARG_TOKEN: &str = "token";
-const ARG_PROJECT_PATH: &str = "project-path";
-const ARG_EDITOR: &str = "editor";
+pub const ARG_TOKEN: &str = "token";
+pub const ARG_PROJECT_PATH: &str = "project-path";
+pub const ARG_EDITOR: &str = "editor";
+pub const ARG_HOST: &str = "host";
+pub const ARG_FORGE: &str = "forge";
+pub const ARG_REMOTE: &str = "remote";
+pub const ARG_UPSTREAM: &str = "upstream";
 pub const ARG_USE_ORGMODE: &str = "use-org-mode";
+pub const ARG_CONVERTER: &str = "converter";
+pub const ARG_FORMAT: &str = "format";
+pub const ARG_FILE: &str = "file";
 pub const ARG_BRANCH: &str = "branch";
+pub const ARG_TEMPLATE: &str = "template";
+pub const ARG_COLUMNS: &str = "columns";
+pub const ARG_OUTPUT: &str = "output";
+pub const ARG_IID: &str = "iid";
+pub const ARG_STATE: &str = "state";
 
 /**
  * Returns the global CLI interaface definition.
@@ -29,7 +49,15 @@ pub fn build_cli<'a, 'b>() -> App<'a, 'b> {
         .setting(AppSettings::ArgRequiredElseHelp)
         .subcommand(commands::edit_merge_request::get_subcommand())
         .subcommand(commands::create_issue::get_subcommand())
+        .subcommand(commands::edit_issue::get_subcommand())
+        .subcommand(commands::create_mr::get_subcommand())
         .subcommand(commands::open_mr::get_subcommand())
+        .subcommand(commands::list_mrs::get_subcommand())
+        .subcommand(commands::list_issues::get_subcommand())
+        .subcommand(commands::show_mr::get_subcommand())
+        .subcommand(commands::show_issue::get_subcommand())
+        .subcommand(commands::drafts::get_subcommand())
+        .subcommand(commands::config::get_subcommand())
         .arg(
             Arg::with_name("v")
                 .short("v")
@@ -40,19 +68,74 @@ pub fn build_cli<'a, 'b>() -> App<'a, 'b> {
         .arg(
             Arg::with_name(ARG_TOKEN)
                 .short("t")
-                .required(true)
+                .required(false)
                 .takes_value(true)
-                .help("The authentication token to be sent as Bearer token to the remote forge.")
+                .help("The authentication token to be sent as Bearer token to the remote forge. Prefer 'token_command', 'token_env' or 'token_source' in the config file to avoid storing it in plain text.")
                 .env("FORGE_CLI_TOKEN"),
         )
         .arg(
             Arg::with_name(ARG_PROJECT_PATH)
                 .short("p")
-                .required(true)
+                .required(false)
                 .takes_value(true)
-                .help("The path of the current project, e.g. 'myusername/myproject' or 'mygroup/myproject'")
+                .help("The path of the current project, e.g. 'myusername/myproject' or 'mygroup/myproject'. On Github, this is '<owner>/<repository>'. Default is the path derived from the URL of the git remote")
                 .env("FORGE_CLI_PROJECT_PATH"),
         )
+        .arg(
+            Arg::with_name(ARG_REMOTE)
+                .short("r")
+                .long("remote")
+                .required(false)
+                .takes_value(true)
+                .help("The git remote used to derive the project path, host and forge if no project path is given. Default is 'origin'")
+                .env("FORGE_CLI_REMOTE"),
+        )
+        .arg(
+            Arg::with_name(ARG_UPSTREAM)
+                .long("upstream")
+                .required(false)
+                .takes_value(true)
+                .help("The path of the upstream project if the current project is a fork, e.g. 'mygroup/myproject'. Merge requests are then looked up in and opened against the upstream project, as are all other commands. Can also be set as 'upstream' in the repository-local .forge.toml")
+                .env("FORGE_CLI_UPSTREAM"),
+        )
+        .arg(
+            Arg::with_name(ARG_HOST)
+                .short("H")
+                .long("host")
+                .required(false)
+                .takes_value(true)
+                .help("The base URL of the remote forge, e.g. 'https://gitlab.example.com'. Can also be set as 'host' in ~/.config/forge/config.toml. Default is the host of the git remote or the public instance of the chosen forge, e.g. 'https://gitlab.com'")
+                .env("FORGE_CLI_HOST"),
+        )
+        .arg(
+            Arg::with_name(ARG_FORGE)
+                .short("f")
+                .long("forge")
+                .required(false)
+                .takes_value(true)
+                .possible_values(FORGE_KINDS)
+                .help("The kind of the remote forge. Can also be set as 'forge' in ~/.config/forge/config.toml. Default is guessed from the host of the git remote, falling back to 'gitlab'")
+                .env("FORGE_CLI_FORGE"),
+        )
+        .arg(
+            Arg::with_name(ARG_CONVERTER)
+                .long("converter")
+                .required(false)
+                .takes_value(true)
+                .possible_values(ORG_CONVERTERS)
+                .help("The converter used to translate between markdown and ORG in ORG mode. 'pandoc' requires pandoc to be installed. Can also be set as 'converter' in ~/.config/forge/config.toml. Default is 'builtin'")
+                .env("FORGE_CLI_CONVERTER"),
+        )
+        .arg(
+            Arg::with_name(ARG_OUTPUT)
+                .long("output")
+                .required(false)
+                .takes_value(true)
+                .possible_values(OUTPUT_FORMATS)
+                .default_value("text")
+                .help("The format of the output. 'json' prints a single JSON document per command, e.g. the iid and URL of a created issue, and reports errors as JSON object with a 'kind', 'message' and 'causes'")
+                .env("FORGE_CLI_OUTPUT"),
+        )
 }
 
 /**
@@ -63,7 +146,19 @@ pub fn arg_edit_orgmode<'a, 'b>() -> Arg<'a, 'b> {
 		.short("o")
 		.takes_value(false)
 		.required(false)
-		.help("If set, issues and merge requests are translated from markdown to org mode for local editing")
+		.help("If set, issues and merge requests are translated from markdown to org mode for local editing. Same as '--format org'")
+}
+
+/**
+ * Returns the definition of the CLI argument '--format', which is used by different sub-commands.
+ */
+pub fn arg_format<'a, 'b>() -> Arg<'a, 'b> {
+    Arg::with_name(ARG_FORMAT)
+        .long("format")
+        .takes_value(true)
+        .required(false)
+        .conflicts_with(ARG_USE_ORGMODE)
+        .help("The format issues and merge requests are edited in, e.g. 'org', 'asciidoc', 'rst', 'djot' or a format configured in a [formats.<name>] section of the config file. All formats except markdown and org require pandoc, unless configured otherwise. Can also be set as FORGE_CLI_FORMAT environment variable, which is overridden by -o. Default is 'markdown'")
 }
 
 pub fn arg_branch<'a, 'b>() -> Arg<'a, 'b> {
@@ -74,12 +169,58 @@ pub fn arg_branch<'a, 'b>() -> Arg<'a, 'b> {
         .help("The name of the branch of the merge request to edit. Default is the currently checked out branch")
         .env("FORGE_CLI_BRANCH")
 }
+
+/**
+ * Returns the definition of the CLI argument '--iid', which chooses a merge request by its iid instead of its branch.
+ */
+pub fn arg_mr_iid<'a, 'b>() -> Arg<'a, 'b> {
+    Arg::with_name(ARG_IID)
+        .long("iid")
+        .takes_value(true)
+        .required(false)
+        .help("The iid of the merge request, e.g. '12'. Takes precedence over -b and --state")
+}
+
+/**
+ * Returns the definition of the CLI argument '--state', which chooses between several merge requests of a branch.
+ */
+pub fn arg_mr_state<'a, 'b>() -> Arg<'a, 'b> {
+    Arg::with_name(ARG_STATE)
+        .long("state")
+        .takes_value(true)
+        .required(false)
+        .possible_values(MERGE_REQUEST_STATES)
+        .help("The state of the merge request if the branch has several. Default is to prefer open merge requests over merged and closed ones. If several merge requests remain, you are asked to choose one")
+}
+/**
+ * Returns the definition of the CLI argument '--template', which is used by different sub-commands.
+ */
+pub fn arg_template<'a, 'b>() -> Arg<'a, 'b> {
+    Arg::with_name(ARG_TEMPLATE)
+        .long("template")
+        .takes_value(true)
+        .required(false)
+        .help("The name of the template to pre-populate the editor buffer with, e.g. 'Bug' for '.gitlab/issue_templates/Bug.md'. If not set and several templates exist, you are asked to choose one")
+}
+
+/**
+ * Returns the definition of the CLI argument '--file', which allows sub-commands to read their input
+ * from a file or stdin instead of opening the editor.
+ */
+pub fn arg_file<'a, 'b>() -> Arg<'a, 'b> {
+    Arg::with_name(ARG_FILE)
+        .long("file")
+        .takes_value(true)
+        .required(false)
+        .help("Reads the text from the given file instead of opening the editor, or from stdin if '-'. The text is expected in the same format as the editor buffer")
+}
+
 pub fn arg_editor<'a, 'b>() -> Arg<'a, 'b> {
     Arg::with_name(ARG_EDITOR)
         .short("e")
         .required(false)
         .takes_value(true)
-        .help("The command name of the editor to use. Cannot be an alias! Default is '$EDITOR'")
+        .help("The editor command, e.g. 'code --wait'. It is split into arguments like in a shell. '{file}' and '{line}' are replaced by the path of the edited file and the line to place the cursor on, e.g. 'vim +{line} {file}'. Without '{file}', the path is appended. Default is $VISUAL, $EDITOR or the editor configured for git")
         .env("FORGE_CLI_EDITOR")
 }
 
@@ -88,19 +229,74 @@ pub fn arg_editor<'a, 'b>() -> Arg<'a, 'b> {
  */
 pub struct GlobalArgs {
     pub token: String,
+    pub host: String,
+    pub forge: ForgeKind,
     pub project_path: String,
-    pub editor_cmd: String,
+    /// path of the upstream project if `project_path` is a fork
+    pub upstream: Option<String>,
+    pub remote: String,
+    /// the editor command, only required by commands which open the editor
+    pub editor_cmd: Option<String>,
+    /// the format of the editor buffer
+    pub format: BufferFormat,
+    /// all available formats, e.g. for resuming drafts created in another format
+    pub formats: Formats,
+    pub labels: Vec<String>,
+    /// the columns shown by list-issues
+    pub issue_columns: Vec<String>,
+    /// the format results are printed in
+    pub output: OutputFormat,
 }
 impl<'a> GlobalArgs {
-    /// returns a new global options struct based on the parsed CLI arguments
+    /// returns a new global options struct based on the parsed CLI arguments, environment and config files
     pub fn from_cli_args(arg_matches: &'a ArgMatches) -> Result<GlobalArgs, Report> {
+        let settings = Settings::resolve(arg_matches)?;
+
+        let project_path = match (settings.project_path, settings.remote_error) {
+            (Some(project_path), _) => project_path.value,
+            (None, Some(err)) => {
+                return Err(err.wrap_err(format!(
+                    "Could not derive the project path from git remote '{}'. Use -p to set it explicitly",
+                    settings.remote.value
+                )))
+            }
+            (None, None) => return Err(eyre!("Missing project path. Use -p to set it")),
+        };
+
+        let token = settings
+            .token
+            .ok_or_else(|| {
+                eyre!("Missing token. Use -t, FORGE_CLI_TOKEN or a token source in the config file")
+            })?
+            .value
+            .resolve(&settings.host.value)
+            .wrap_err("Failed to obtain the authentication token")?;
+
+        let formats = Formats::new(settings.converter.value, &settings.formats);
+        let format = formats.get(&settings.format.value)?;
+
         Ok(GlobalArgs {
-            token: arg_matches.value_of(ARG_TOKEN).unwrap().to_string(),
-            project_path: arg_matches.value_of(ARG_PROJECT_PATH).unwrap().to_string(),
-            editor_cmd: match arg_matches.value_of(ARG_EDITOR) {
-                None => std::env::var("EDITOR").wrap_err("Missing EDITOR environment variable")?,
-                Some(arg) => String::from(arg),
-            },
+            token,
+            host: settings.host.value,
+            forge: settings.forge.value,
+            project_path,
+            upstream: settings.upstream.map(|upstream| upstream.value),
+            remote: settings.remote.value,
+            editor_cmd: settings.editor.map(|editor| editor.value),
+            format,
+            formats,
+            labels: settings.labels.value,
+            issue_columns: settings.issue_columns.value,
+            output: OutputFormat::from_cli_args(arg_matches),
+        })
+    }
+
+    /// returns the editor command, or fails if no editor has been found
+    pub fn editor_cmd(&self) -> Result<&str, Report> {
+        self.editor_cmd.as_deref().ok_or_else(|| {
+            eyre!(
+                "No editor found. Use -e, 'editor' in the config file, VISUAL or EDITOR to set it"
+            )
         })
     }
 }
@@ -0,0 +1,53 @@
+use clap::{App, AppSettings, ArgMatches, SubCommand};
+use eyre::eyre;
+
+use crate::{
+    output::{print_json, OutputFormat},
+    settings::Settings,
+};
+
+use super::CommandResult;
+
+pub const CMD_IDENTIFIER: &str = "config";
+const CMD_ABOUT: &str = "Inspects the configuration of this CLI.";
+
+const CMD_SHOW: &str = "show";
+const CMD_SHOW_ABOUT: &str = r#"
+Prints the effective value of every setting and where it came from.
+Settings are resolved in the following order, the first one found wins:
+1. CLI arguments
+2. environment variables
+3. the repository-local .forge.toml (host profile first, then top level entries)
+4. the git remote (only if no project path is set by one of the above)
+5. ~/.config/forge/config.toml (host profile first, then top level entries)
+6. built-in defaults
+The host, token sources, editor and formats can only be set in ~/.config/forge/config.toml.
+"#;
+
+/// returns the clap definition for this sub-command
+pub fn get_subcommand<'a, 'b>() -> App<'a, 'b> {
+    SubCommand::with_name(CMD_IDENTIFIER)
+        .about(CMD_ABOUT)
+        .setting(AppSettings::SubcommandRequiredElseHelp)
+        .subcommand(SubCommand::with_name(CMD_SHOW).about(CMD_SHOW_ABOUT))
+}
+
+/**
+ * Runs the config sub-command. In contrast to other commands, this command only requires the
+ * top level CLI arguments, because it must work even if settings such as the token are missing.
+ */
+pub fn run<'a>(cli_args: &ArgMatches<'a>, args: &ArgMatches<'a>) -> CommandResult {
+    match args.subcommand() {
+        (CMD_SHOW, Some(_)) => {
+            let settings = Settings::resolve(cli_args)?;
+            match OutputFormat::from_cli_args(cli_args) {
+                OutputFormat::Json => print_json(&settings.describe_json()),
+                OutputFormat::Text => {
+                    print!("{}", settings.describe());
+                    Ok(())
+                }
+            }
+        }
+        (cmd, _) => Err(eyre!("Invalid or unknown command: config {}", cmd)),
+    }
+}
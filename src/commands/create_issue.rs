@@ -1,92 +1,170 @@
-use std::fs::File;
-
-use clap::{App, ArgMatches, SubCommand};
-use cmd_lib::run_cmd;
-use eyre::Context;
-use std::io::Read;
-use tempfile::tempdir;
+use clap::{App, Arg, ArgMatches, SubCommand};
+use eyre::{Context, Report};
 
 use crate::{
-    cli::{arg_edit_orgmode, GlobalArgs, ARG_USE_ORGMODE},
-    queries::gitlab_create_issue::create_issue,
+    cli::{
+        arg_edit_orgmode, arg_editor, arg_file, arg_format, arg_template, GlobalArgs, ARG_FILE,
+        ARG_TEMPLATE,
+    },
+    convert::BufferFormat,
+    drafts::{Draft, DraftKind},
+    editor::{
+        buffer_file_name, check_title, edit_buffer, from_buffer, join_title, read_input,
+        split_required_title, to_buffer,
+    },
+    forges::Forge,
+    output::{print_json, OutputFormat},
+    templates::{select_template, TemplateKind},
 };
 
 use super::CommandResult;
 
 pub const CMD_IDENTIFIER: &str = "create-issue";
 const CMD_ABOUT: &str = r#"
-Opens a new empty buffer in the system text editor and uploads the content to the remote host as a new issue after the editor is closed.
+Opens a new buffer in the system text editor and uploads the content to the remote host as a new issue after the editor is closed.
 The format used is similar to git commits:
 1. The first line of text is regarded as issue title
 2. There must be an empty line between the title and the body.
 3. All following lines are uploaded as issue description.
 
-When called with the -o flag, the input is expected to follow the ORG format and is automatically converted to markdown before upload.
+If the repository contains issue templates in .gitlab/issue_templates, the buffer is pre-populated with the template chosen via --template or interactively.
+
+Lines below the scissors line ('# --- >8 ---') only show context and are ignored. If the title is empty, the creation is aborted.
+
+When called with the -o flag, the input is expected to follow the ORG format and is automatically converted to markdown before upload. Other formats, e.g. AsciiDoc, can be chosen via --format.
+
+To create an issue without opening the editor, e.g. in scripts, pass the text via --title and --description, or via --file, either as path of a file or as '-' for reading it from stdin. The text is split into title and description the same way as the editor buffer.
+
+If the upload fails, the text is kept as draft, which can be uploaded again using 'forge drafts resume'.
 "#;
 
+const ARG_TITLE: &str = "title";
+const ARG_DESCRIPTION: &str = "description";
+
 /// returns the clap definition for the edit merge-request sub-command
 pub fn get_subcommand<'a, 'b>() -> App<'a, 'b> {
     SubCommand::with_name(CMD_IDENTIFIER)
         .about(CMD_ABOUT)
         .alias("ci")
         .arg(arg_edit_orgmode())
+        .arg(arg_format())
+        .arg(arg_editor())
+        .arg(arg_template())
+        .arg(arg_file().conflicts_with_all(&[ARG_TITLE, ARG_DESCRIPTION]))
+        .arg(
+            Arg::with_name(ARG_TITLE)
+                .long("title")
+                .takes_value(true)
+                .required(false)
+                .help("The title of the new issue. If set, the editor is not opened"),
+        )
+        .arg(
+            Arg::with_name(ARG_DESCRIPTION)
+                .long("description")
+                .takes_value(true)
+                .required(false)
+                .requires(ARG_TITLE)
+                .help("The description of the new issue, in the same format as the editor buffer. Default is the template chosen via --template, if any"),
+        )
 }
 
-pub async fn run<'a>(args: &ArgMatches<'a>, global_args: &GlobalArgs) -> CommandResult {
-    let use_org = args.is_present(ARG_USE_ORGMODE);
-    let tmp_file_ext = if use_org { "org" } else { "md" };
-    let tmp_dir = tempdir().wrap_err("Could not create temp dir to store issue")?;
-    let tmp_file_path = tmp_dir.path().join(format!("new_issue.{}", tmp_file_ext));
-    let editor = &global_args.editor_cmd;
-
-    // we assume the editor creates the file if it does not exist:
-    run_cmd!($editor $tmp_file_path)?;
-
-    // convert the ORG description to markdown if necessary:
-    let path_to_upload = if use_org {
-        let mut markdown_path = tmp_file_path.clone();
-        markdown_path.set_extension("md");
-        run_cmd!(pandoc -f org -t markdown $tmp_file_path -o $markdown_path)
-            .wrap_err("Could not convert the description to markdown")?;
-        markdown_path
-    } else {
-        tmp_file_path
+/**
+ * Opens the editor to enter the title and description of the new issue and returns the edited buffer.
+ */
+fn edit_new_issue(args: &ArgMatches, global_args: &GlobalArgs) -> Result<String, Report> {
+    let prefill = match select_template(TemplateKind::Issue, args.value_of(ARG_TEMPLATE))? {
+        Some(template) => join_title("", &template),
+        None => String::new(),
     };
 
-    let mut tmp_file = File::open(&path_to_upload).wrap_err_with(|| {
-        format!("Could not open {:?} to read the issue description", {
-            &path_to_upload
-        })
-    })?;
+    let format = &global_args.format;
+    let mut comments = vec![format!(
+        "Creating a new issue in {} on {}.",
+        global_args.project_path, global_args.host
+    )];
+    if !global_args.labels.is_empty() {
+        comments.push(format!("Labels: {}", global_args.labels.join(", ")));
+    }
+    comments.push(String::from(
+        "The first line is used as title, an empty title aborts the creation.",
+    ));
+    edit_buffer(
+        &buffer_file_name("new_issue", format),
+        &to_buffer(&prefill, format)?,
+        1,
+        &comments,
+        global_args.editor_cmd()?,
+    )
+}
 
-    let mut issue_description = String::new();
-    File::read_to_string(&mut tmp_file, &mut issue_description)
-        .wrap_err("Failed to read the issue description from file")?;
+/**
+ * Runs the create-issue sub-command.
+ */
+pub async fn run<'a>(
+    args: &ArgMatches<'a>,
+    global_args: &GlobalArgs,
+    forge: &dyn Forge,
+) -> CommandResult {
+    let format = &global_args.format;
+    // the editor is only opened if the text is not passed non-interactively:
+    let buffer = match (args.value_of(ARG_FILE), args.value_of(ARG_TITLE)) {
+        (Some(path), _) => read_input(path)?,
+        (None, Some(title)) => {
+            let description = match args.value_of(ARG_DESCRIPTION) {
+                Some(description) => description.to_string(),
+                // templates are only used if requested explicitly, as nobody can choose one interactively:
+                None => match args.value_of(ARG_TEMPLATE) {
+                    Some(name) => to_buffer(
+                        &select_template(TemplateKind::Issue, Some(name))?.unwrap_or_default(),
+                        format,
+                    )?,
+                    None => String::new(),
+                },
+            };
+            join_title(title, &description)
+        }
+        (None, None) => edit_new_issue(args, global_args)?,
+    };
+    check_title(&buffer, format)?;
+
+    let labels = global_args.labels.clone();
+    let draft = Draft::create(
+        DraftKind::CreateIssue {
+            labels: labels.clone(),
+        },
+        &buffer,
+        format,
+        global_args,
+    )?;
+    draft
+        .upload(upload(&buffer, format, &labels, global_args.output, forge))
+        .await
+}
 
-    // cleanup all created tmp resources:
-    tmp_dir.close()?;
+/**
+ * Creates a new issue from the given editor buffer and prints its URL, or its iid and URL as JSON.
+ */
+pub async fn upload(
+    buffer: &str,
+    format: &BufferFormat,
+    labels: &[String],
+    output: OutputFormat,
+    forge: &dyn Forge,
+) -> CommandResult {
+    let issue_text = from_buffer(buffer, format)?;
 
     // split the user input by the first empty line to separate issue title and issue description:
-    let splitted_desc: Vec<&str> = issue_description.splitn(2, "\n\n").collect();
-    let (title, description) = if splitted_desc.len() == 1 {
-        (String::from(splitted_desc[0]), None)
-    } else {
-        (
-            String::from(splitted_desc[0]),
-            Some(String::from(splitted_desc[1])),
-        )
-    };
+    let (title, description) = split_required_title(&issue_text)?;
 
-    let created_issue = create_issue(
-        &global_args.token,
-        &global_args.project_path,
-        &title,
-        &description,
-    )
-    .await
-    .wrap_err("Failed to create issue")?;
+    let created_issue = forge
+        .create_issue(&title, &description, labels)
+        .await
+        .wrap_err("Failed to create issue")?;
 
-    println!("New issue created at {}", created_issue.web_url);
+    match output {
+        OutputFormat::Json => print_json(&created_issue)?,
+        OutputFormat::Text => println!("New issue created at {}", created_issue.web_url),
+    }
 
     Ok(())
 }
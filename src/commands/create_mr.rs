@@ -0,0 +1,187 @@
+use clap::{App, Arg, ArgMatches, SubCommand};
+use eyre::Context;
+use log::{debug, warn};
+
+use crate::{
+    cli::{
+        arg_branch, arg_edit_orgmode, arg_editor, arg_format, arg_template, GlobalArgs,
+        ARG_TEMPLATE,
+    },
+    convert::BufferFormat,
+    drafts::{Draft, DraftKind},
+    editor::{
+        buffer_file_name, check_title, edit_buffer, from_buffer, join_title, split_required_title,
+        split_title, to_buffer,
+    },
+    forges::Forge,
+    get_branch_name, get_commit_messages,
+    output::{print_json, OutputFormat},
+    templates::{select_template, TemplateKind},
+};
+
+use super::CommandResult;
+
+pub const CMD_IDENTIFIER: &str = "create-mr";
+const CMD_ABOUT: &str = r#"
+Creates a new merge request for the currently checked out branch (if no branch specified via -b).
+Opens a buffer pre-filled with the commit messages of the branch in the system text editor and uploads the content as title and description of the new merge request after the editor is closed.
+The format used is similar to git commits:
+1. The first line of text is regarded as merge request title
+2. There must be an empty line between the title and the body.
+3. All following lines are uploaded as merge request description.
+
+If the repository contains merge request templates in .gitlab/merge_request_templates, the description is pre-populated with the template chosen via --template or interactively instead.
+
+Lines below the scissors line ('# --- >8 ---') only show context and are ignored. If the title is empty, the creation is aborted.
+
+When called with the -o flag, the input is expected to follow the ORG format and is automatically converted to markdown before upload. Other formats, e.g. AsciiDoc, can be chosen via --format.
+
+If the upload fails, the text is kept as draft, which can be uploaded again using 'forge drafts resume'.
+"#;
+
+const ARG_TARGET_BRANCH: &str = "target-branch";
+
+/// returns the clap definition for this sub-command
+pub fn get_subcommand<'a, 'b>() -> App<'a, 'b> {
+    SubCommand::with_name(CMD_IDENTIFIER)
+        .about(CMD_ABOUT)
+        .alias("cm")
+        .arg(arg_branch())
+        .arg(
+            Arg::with_name(ARG_TARGET_BRANCH)
+                .short("T")
+                .long("target")
+                .takes_value(true)
+                .required(false)
+                .help("The name of the branch to merge into. Default is the default branch of the project")
+                .env("FORGE_CLI_TARGET_BRANCH"),
+        )
+        .arg(arg_edit_orgmode())
+        .arg(arg_format())
+        .arg(arg_editor())
+        .arg(arg_template())
+}
+
+/**
+ * Returns the initial content of the editor buffer based on the given commit messages:
+ * A single commit message is used as is, multiple commits are listed below the branch name.
+ */
+fn prefill_from_commits(source_branch: &str, messages: &[String]) -> String {
+    match messages {
+        [] => String::new(),
+        [message] => format!("{}\n", message),
+        _ => {
+            let commit_list: Vec<String> = messages
+                .iter()
+                .map(|message| format!("- {}", message.lines().next().unwrap_or_default()))
+                .collect();
+            join_title(source_branch, &format!("{}\n", commit_list.join("\n")))
+        }
+    }
+}
+
+/**
+ * Runs the create-mr sub-command.
+ */
+pub async fn run<'a>(
+    args: &ArgMatches<'a>,
+    global_args: &GlobalArgs,
+    forge: &dyn Forge,
+) -> CommandResult {
+    let source_branch = get_branch_name(args)?;
+    let target_branch = match args.value_of(ARG_TARGET_BRANCH) {
+        Some(branch) => branch.to_string(),
+        None => forge
+            .get_default_branch()
+            .await
+            .wrap_err("Failed to fetch the default branch of the project")?,
+    };
+    debug!("source branch: {}", source_branch);
+    debug!("target branch: {}", target_branch);
+
+    // the commit messages are only a suggestion, so we don't fail if they are not available:
+    let revision_range = format!(
+        "{}/{}..{}",
+        global_args.remote, target_branch, source_branch
+    );
+    let prefill = match get_commit_messages(&revision_range) {
+        Ok(messages) => prefill_from_commits(&source_branch, &messages),
+        Err(err) => {
+            warn!(
+                "Could not read the commit messages of {}: {}",
+                revision_range, err
+            );
+            String::new()
+        }
+    };
+
+    // a template replaces the description derived from the commit messages, but keeps the title:
+    let prefill = match select_template(TemplateKind::MergeRequest, args.value_of(ARG_TEMPLATE))? {
+        Some(template) => join_title(&split_title(&prefill).0, &template),
+        None => prefill,
+    };
+
+    let format = &global_args.format;
+    let comments = vec![
+        format!(
+            "Creating a merge request from {} into {} in {} on {}.",
+            source_branch, target_branch, global_args.project_path, global_args.host
+        ),
+        String::from("The first line is used as title, an empty title aborts the creation."),
+    ];
+    let buffer = edit_buffer(
+        &buffer_file_name("new_merge_request", format),
+        &to_buffer(&prefill, format)?,
+        1,
+        &comments,
+        global_args.editor_cmd()?,
+    )?;
+    check_title(&buffer, format)?;
+
+    let draft = Draft::create(
+        DraftKind::CreateMergeRequest {
+            source_branch: source_branch.clone(),
+            target_branch: target_branch.clone(),
+        },
+        &buffer,
+        format,
+        global_args,
+    )?;
+    draft
+        .upload(upload(
+            &source_branch,
+            &target_branch,
+            &buffer,
+            format,
+            global_args.output,
+            forge,
+        ))
+        .await
+}
+
+/**
+ * Creates a new merge request from the given editor buffer and prints its URL, or its iid and URL as JSON.
+ */
+pub async fn upload(
+    source_branch: &str,
+    target_branch: &str,
+    buffer: &str,
+    format: &BufferFormat,
+    output: OutputFormat,
+    forge: &dyn Forge,
+) -> CommandResult {
+    let mr_text = from_buffer(buffer, format)?;
+    let (title, description) = split_required_title(&mr_text)?;
+
+    let created_mr = forge
+        .create_merge_request(source_branch, target_branch, &title, &description)
+        .await
+        .wrap_err("Failed to create merge request")?;
+
+    match output {
+        OutputFormat::Json => print_json(&created_mr)?,
+        OutputFormat::Text => println!("New merge request created at {}", created_mr.web_url),
+    }
+
+    Ok(())
+}
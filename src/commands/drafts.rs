@@ -0,0 +1,215 @@
+use clap::{App, AppSettings, Arg, ArgMatches, SubCommand};
+use eyre::{eyre, Context, Report};
+use serde_json::json;
+
+use crate::{
+    cli::{arg_editor, GlobalArgs},
+    drafts::{list_drafts, Draft, DraftKind},
+    editor::edit_file,
+    forges::get_forge,
+    output::{print_json, OutputFormat},
+};
+
+use super::{create_issue, create_mr, edit_issue, edit_merge_request, CommandResult};
+
+pub const CMD_IDENTIFIER: &str = "drafts";
+const CMD_ABOUT: &str = r#"
+Manages drafts of issues and merge requests which could not be uploaded.
+The text edited by create-issue, edit-issue, create-mr and edit-mr is stored as draft until it has been uploaded successfully.
+"#;
+
+const CMD_LIST: &str = "list";
+const CMD_LIST_ABOUT: &str = "Lists all drafts.";
+const CMD_RESUME: &str = "resume";
+const CMD_RESUME_ABOUT: &str = r#"
+Retries to upload the given draft. The draft must be resumed for the same project it has been created for.
+When called with --edit, the draft is opened in the editor again before uploading it.
+"#;
+const CMD_DISCARD: &str = "discard";
+const CMD_DISCARD_ABOUT: &str = "Deletes the given draft without uploading it.";
+
+const ARG_ID: &str = "id";
+const ARG_EDIT: &str = "edit";
+
+fn arg_id<'a, 'b>() -> Arg<'a, 'b> {
+    Arg::with_name(ARG_ID)
+        .required(true)
+        .help("The id of the draft, as shown by 'forge drafts list'")
+}
+
+/// returns the clap definition for this sub-command
+pub fn get_subcommand<'a, 'b>() -> App<'a, 'b> {
+    SubCommand::with_name(CMD_IDENTIFIER)
+        .about(CMD_ABOUT)
+        .setting(AppSettings::SubcommandRequiredElseHelp)
+        .subcommand(SubCommand::with_name(CMD_LIST).about(CMD_LIST_ABOUT))
+        .subcommand(
+            SubCommand::with_name(CMD_RESUME)
+                .about(CMD_RESUME_ABOUT)
+                .arg(arg_id())
+                .arg(
+                    Arg::with_name(ARG_EDIT)
+                        .long("edit")
+                        .takes_value(false)
+                        .required(false)
+                        .help("Opens the draft in the editor before uploading it"),
+                )
+                .arg(arg_editor()),
+        )
+        .subcommand(
+            SubCommand::with_name(CMD_DISCARD)
+                .about(CMD_DISCARD_ABOUT)
+                .arg(arg_id()),
+        )
+}
+
+/// loads the draft with the id passed to the given sub-command
+fn load_draft(args: &ArgMatches) -> Result<Draft, Report> {
+    let id = args.value_of(ARG_ID).unwrap_or_default();
+    let id = id
+        .parse()
+        .wrap_err_with(|| format!("Invalid draft id '{}'", id))?;
+    Draft::load(id)
+}
+
+/**
+ * Uploads the given draft using the upload function of the command it has been created by.
+ */
+async fn resume(
+    mut draft: Draft,
+    args: &ArgMatches<'_>,
+    global_args: &GlobalArgs,
+) -> CommandResult {
+    if draft.host != global_args.host || draft.project_path != global_args.project_path {
+        return Err(eyre!(
+            "Draft {} belongs to project {} on {}, but the current project is {} on {}",
+            draft.id,
+            draft.project_path,
+            draft.host,
+            global_args.project_path,
+            global_args.host
+        ));
+    }
+
+    let format = &global_args.formats.get(&draft.format)?;
+    if args.is_present(ARG_EDIT) {
+        draft.text = edit_file(
+            &draft.file_name(format),
+            &draft.text,
+            1,
+            global_args.editor_cmd()?,
+        )?;
+        draft.save()?;
+    }
+
+    let forge = get_forge(global_args)?;
+    let forge = forge.as_ref();
+    let text = &draft.text;
+
+    match &draft.kind {
+        DraftKind::CreateIssue { labels } => {
+            draft
+                .upload(create_issue::upload(
+                    text,
+                    format,
+                    labels,
+                    global_args.output,
+                    forge,
+                ))
+                .await
+        }
+        DraftKind::EditIssue {
+            iid,
+            original_title,
+            original_description,
+        } => {
+            draft
+                .upload(edit_issue::upload(
+                    iid,
+                    original_title,
+                    original_description,
+                    text,
+                    format,
+                    global_args.output,
+                    forge,
+                ))
+                .await
+        }
+        DraftKind::CreateMergeRequest {
+            source_branch,
+            target_branch,
+        } => {
+            draft
+                .upload(create_mr::upload(
+                    source_branch,
+                    target_branch,
+                    text,
+                    format,
+                    global_args.output,
+                    forge,
+                ))
+                .await
+        }
+        DraftKind::EditMergeRequest {
+            iid,
+            original_description,
+            original_fields,
+            ..
+        } => {
+            draft
+                .upload(edit_merge_request::upload(
+                    iid,
+                    original_description,
+                    original_fields,
+                    text,
+                    format,
+                    global_args,
+                    forge,
+                ))
+                .await
+        }
+    }
+}
+
+/**
+ * Runs the drafts sub-command. Only resuming a draft requires the global arguments to be complete.
+ */
+pub async fn run<'a>(cli_args: &ArgMatches<'a>, args: &ArgMatches<'a>) -> CommandResult {
+    let output = OutputFormat::from_cli_args(cli_args);
+    match args.subcommand() {
+        (CMD_LIST, Some(_)) => {
+            let drafts = list_drafts()?;
+            if output == OutputFormat::Json {
+                let drafts = drafts
+                    .iter()
+                    .map(Draft::to_json)
+                    .collect::<Result<Vec<_>, Report>>()?;
+                return print_json(&drafts);
+            }
+            if drafts.is_empty() {
+                println!("No drafts found.");
+            }
+            for draft in drafts {
+                println!("{}", draft.summary());
+            }
+            Ok(())
+        }
+        (CMD_RESUME, Some(cmd_args)) => {
+            let draft = load_draft(cmd_args)?;
+            let global_args = GlobalArgs::from_cli_args(cli_args)?;
+            resume(draft, cmd_args, &global_args).await
+        }
+        (CMD_DISCARD, Some(cmd_args)) => {
+            let draft = load_draft(cmd_args)?;
+            draft.discard()?;
+            match output {
+                OutputFormat::Json => print_json(&json!({ "id": draft.id, "discarded": true })),
+                OutputFormat::Text => {
+                    println!("Discarded draft {}.", draft.id);
+                    Ok(())
+                }
+            }
+        }
+        (cmd, _) => Err(eyre!("Invalid or unknown command: drafts {}", cmd)),
+    }
+}
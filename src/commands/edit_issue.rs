@@ -0,0 +1,149 @@
+use clap::{App, Arg, ArgMatches, SubCommand};
+use eyre::Context;
+use log::{debug, info};
+use serde_json::json;
+
+use crate::{
+    cli::{arg_edit_orgmode, arg_editor, arg_format, GlobalArgs},
+    convert::BufferFormat,
+    drafts::{Draft, DraftKind},
+    editor::{
+        buffer_file_name, check_changed, check_title, edit_buffer, from_edited_buffer, join_title,
+        split_required_title, to_buffer,
+    },
+    forges::Forge,
+    output::{print_json, OutputFormat},
+};
+
+use super::CommandResult;
+
+pub const CMD_IDENTIFIER: &str = "edit-issue";
+const CMD_ABOUT: &str = r#"
+Downloads the title and description of the issue with the given iid into a temporary file, opens it in the system text editor and updates the issue on the remote host after the editor is closed.
+The format used is the same as for create-issue:
+1. The first line of text is regarded as issue title
+2. There must be an empty line between the title and the body.
+3. All following lines are uploaded as issue description.
+
+Lines below the scissors line ('# --- >8 ---') only show context and are ignored. If nothing is changed or the title is empty, the issue is not updated.
+
+When called with the -o flag, the downloaded markdown text is converted to ORG and back to markdown before and after editing it. Unless pandoc is chosen via --converter, blocks which were not changed keep their original markdown. Other formats, e.g. AsciiDoc, can be chosen via --format.
+
+If the upload fails, the text is kept as draft, which can be uploaded again using 'forge drafts resume'.
+"#;
+
+const ARG_IID: &str = "iid";
+
+/// returns the clap definition for the edit issue sub-command
+pub fn get_subcommand<'a, 'b>() -> App<'a, 'b> {
+    SubCommand::with_name(CMD_IDENTIFIER)
+        .about(CMD_ABOUT)
+        .alias("ei")
+        .arg(
+            Arg::with_name(ARG_IID)
+                .required(true)
+                .help("The iid of the issue to edit, e.g. 42"),
+        )
+        .arg(arg_edit_orgmode())
+        .arg(arg_format())
+        .arg(arg_editor())
+}
+
+/**
+ * Runs the edit-issue sub-command.
+ */
+pub async fn run<'a>(
+    args: &ArgMatches<'a>,
+    global_args: &GlobalArgs,
+    forge: &dyn Forge,
+) -> CommandResult {
+    let iid = args.value_of(ARG_IID).unwrap_or_default();
+    debug!("issue: {}", iid);
+
+    let issue = forge.get_issue(iid).await.wrap_err_with(|| {
+        format!(
+            "Failed to fetch issue {} of project {}",
+            iid, global_args.project_path
+        )
+    })?;
+
+    let format = &global_args.format;
+    let original_buffer = to_buffer(&join_title(&issue.title, &issue.description), format)?;
+    let comments = vec![
+        format!("Editing issue {}: {}", issue.iid, issue.web_url),
+        String::from(
+            "The first line is used as title. If nothing is changed, the issue is not updated.",
+        ),
+    ];
+    let buffer = edit_buffer(
+        &buffer_file_name(&format!("issue_{}", issue.iid), format),
+        &original_buffer,
+        1,
+        &comments,
+        global_args.editor_cmd()?,
+    )?;
+    check_changed(&original_buffer, &buffer)?;
+    check_title(&buffer, format)?;
+
+    let draft = Draft::create(
+        DraftKind::EditIssue {
+            iid: issue.iid.clone(),
+            original_title: issue.title.clone(),
+            original_description: issue.description.clone(),
+        },
+        &buffer,
+        format,
+        global_args,
+    )?;
+    draft
+        .upload(upload(
+            &issue.iid,
+            &issue.title,
+            &issue.description,
+            &buffer,
+            format,
+            global_args.output,
+            forge,
+        ))
+        .await
+}
+
+/**
+ * Updates the issue with the given iid from the given editor buffer.
+ * Only the title and description which differ from the original ones are uploaded.
+ * With JSON output, the iid of the updated issue is printed.
+ */
+pub async fn upload(
+    iid: &str,
+    original_title: &str,
+    original_description: &str,
+    buffer: &str,
+    format: &BufferFormat,
+    output: OutputFormat,
+    forge: &dyn Forge,
+) -> CommandResult {
+    let original_text = join_title(original_title, original_description);
+    let issue_text = from_edited_buffer(&original_text, buffer, format)?;
+    let (title, description) = split_required_title(&issue_text)?;
+    let title = title.trim().to_string();
+    let description = description.unwrap_or_default();
+
+    // only the changed attributes are uploaded, the buffer may differ after the conversion only:
+    let title = Some(title).filter(|title| title != original_title);
+    let description = Some(description).filter(|description| description != original_description);
+    if title.is_none() && description.is_none() {
+        info!("Issue {} is unchanged, nothing to update.", iid);
+    } else {
+        forge
+            .update_issue(iid, &title, &description)
+            .await
+            .wrap_err("Failed to update the issue")?;
+
+        info!("Updated issue {} successfully.", iid);
+    }
+    if output == OutputFormat::Json {
+        print_json(&json!({ "iid": iid }))?;
+    }
+
+    Ok(())
+}
@@ -1,150 +1,298 @@
-use clap::{App, ArgMatches, SubCommand};
-use cmd_lib::run_cmd;
-use eyre::{Context, Report};
-use log::{debug, info, trace};
-use std::fs::File;
-use std::io::{Read, Write};
-use tempfile::{self, tempdir};
+use clap::{App, Arg, ArgMatches, SubCommand};
+use eyre::{eyre, Context, Report};
+use log::{debug, info, trace, warn};
+use serde_json::json;
 
 use crate::{
-    cli::{arg_branch, arg_edit_orgmode, arg_editor, GlobalArgs, ARG_USE_ORGMODE},
-    get_branch_name,
-    queries::gitlab_get_mr::get_merge_request,
-    queries::gitlab_update_mr_desc::update_merge_request_desc,
+    cli::{
+        arg_branch, arg_edit_orgmode, arg_editor, arg_file, arg_format, arg_mr_iid, arg_mr_state,
+        arg_template, GlobalArgs, ARG_FILE, ARG_TEMPLATE,
+    },
+    convert::BufferFormat,
+    drafts::{Draft, DraftKind},
+    editor::{
+        buffer_file_name, check_changed, check_not_empty, edit_buffer, edit_file,
+        from_edited_buffer, read_input, to_buffer,
+    },
+    forges::Forge,
+    front_matter::MergeRequestFields,
+    get_merge_request,
+    output::{print_json, OutputFormat},
+    templates::{select_template, TemplateKind},
 };
 
 use super::CommandResult;
 
 pub const CMD_IDENTIFIER: &str = "edit-mr";
 const CMD_ABOUT: &str = r#"
-Guesses the merge request related to the currently checked out branch (if no branch specified via -b) and then will:
-1. download its description into a temporary file,
-2. open the system editor to edit the merge request description,
+Guesses the merge request related to the currently checked out branch (if no branch specified via -b or iid via --iid) and then will:
+1. download its title, labels, assignees, reviewers, milestone, draft status and description into a temporary file,
+2. open the system editor to edit them,
 3. update the merge request on the remote host.
 
-When called with the -o flag, the downloaded markdown description is converted to ORG and back to markdown before and after editing it.
+If the branch has several merge requests, open ones are preferred. Otherwise --state chooses between them, or you are asked to choose one.
+
+The attributes are listed in a TOML front matter block between '+++' lines, followed by the markdown description. Attributes removed from the block are left unchanged, an empty milestone removes the milestone.
+
+If the description is empty or a template is passed via --template, a merge request template from .gitlab/merge_request_templates is appended to the description before editing it.
+
+If the description was changed on the remote host while editing it, both changes are merged. Conflicting changes are marked in the text, which is opened in the editor again to resolve them before uploading it.
+
+To update the merge request without opening the editor, e.g. in scripts, pass the new text via --file, either as path of a file or as '-' for reading it from stdin. If the text has no front matter block, only the description is updated. Alternatively, --append and --prepend add the given text to the end or the beginning of the description.
+
+If the upload fails, the edited text is kept as draft, which can be uploaded again using 'forge drafts resume'.
+
+Lines below the scissors line ('# --- >8 ---') only show context and are ignored. If nothing is changed or the text is empty, the merge request is not updated.
+
+When called with the -o flag, the attributes are listed in an ORG property drawer (e.g. ':LABELS: bug, backend') instead and the downloaded markdown description is converted to ORG and back to markdown before and after editing it. Unless pandoc is chosen via --converter, paragraphs and other blocks which were not changed keep their original markdown. Other formats, e.g. AsciiDoc, can be chosen via --format.
 "#;
 
+const ARG_APPEND: &str = "append";
+const ARG_PREPEND: &str = "prepend";
+
 /// returns the clap definition for the edit merge-request sub-command
 pub fn get_subcommand<'a, 'b>() -> App<'a, 'b> {
     SubCommand::with_name(CMD_IDENTIFIER)
         .about(CMD_ABOUT)
         .alias("em")
         .arg(arg_branch())
+        .arg(arg_mr_iid())
+        .arg(arg_mr_state())
         .arg(arg_edit_orgmode())
+        .arg(arg_format())
         .arg(arg_editor())
+        .arg(arg_template())
+        .arg(arg_file().conflicts_with_all(&[ARG_APPEND, ARG_PREPEND]))
+        .arg(
+            Arg::with_name(ARG_APPEND)
+                .long("append")
+                .takes_value(true)
+                .required(false)
+                .help("Appends the given text to the description without opening the editor"),
+        )
+        .arg(
+            Arg::with_name(ARG_PREPEND)
+                .long("prepend")
+                .takes_value(true)
+                .required(false)
+                .help("Prepends the given text to the description without opening the editor"),
+        )
+}
+
+/// returns whether the given text contains unresolved conflict markers
+fn has_conflict_markers(text: &str) -> bool {
+    text.lines()
+        .any(|line| line.starts_with("<<<<<<<") || line.starts_with(">>>>>>>"))
 }
 
 /**
- * Writes the given description to a temporary file and allows the user to edit it.
- * Returns the edited description. Depending on the given args, it may convert the description
- * before and after editing it.
+ * Merges the local changes of the description with the changes made on the remote host since it was
+ * fetched. `original` is the description as fetched before editing it.
+ * If the changes conflict, the merged text containing conflict markers is opened in the editor to resolve them.
  */
-fn edit_mr_description(
-    mr_iid: &String,
-    description: &String,
-    editor_command: &String,
-    convert_to_org: bool,
+fn merge_remote_changes(
+    original: &str,
+    local: &str,
+    remote: &str,
+    iid: &str,
+    editor_command: &str,
 ) -> Result<String, Report> {
-    let tmp_dir = tempdir().wrap_err("Could not create temp dir")?;
+    if remote == original || remote == local {
+        return Ok(local.to_string());
+    }
 
-    // write original description to temp file:
-    let orig_desc_file_path = tmp_dir.path().join(format!("{}.md", mr_iid));
-    let orig_desc_file = File::create(&orig_desc_file_path)?;
-    write!(&orig_desc_file, "{}", description)?;
+    warn!("The description of merge request {} was changed remotely while editing it, merging the changes", iid);
+    match diffy::merge(original, local, remote) {
+        Ok(merged) => Ok(merged),
+        Err(conflicts) => {
+            warn!("The remote changes conflict with your changes, please resolve the conflicts in the editor");
+            // place the cursor on the first conflict:
+            let line = conflicts
+                .lines()
+                .position(|line| line.starts_with("<<<<<<<"))
+                .unwrap_or_default()
+                + 1;
+            let resolved = edit_file(
+                &format!("{}_conflicts.md", iid),
+                &conflicts,
+                line,
+                editor_command,
+            )?;
+            if has_conflict_markers(&resolved) {
+                Err(eyre!(
+                    "The description still contains conflict markers, the merge request was not updated"
+                ))
+            } else {
+                Ok(resolved)
+            }
+        }
+    }
+}
 
-    let mut org_file_path = orig_desc_file_path.clone();
-    org_file_path.set_extension("org");
+/**
+ * Runs the edit-mr sub-command.
+ */
+pub async fn run<'a>(
+    args: &ArgMatches<'a>,
+    global_args: &GlobalArgs,
+    forge: &dyn Forge,
+) -> CommandResult {
+    let format = &global_args.format;
 
-    // if required, write converted description to new temp file:
-    let path_to_edit = if convert_to_org {
-        run_cmd!(pandoc -f markdown -t org $orig_desc_file_path -o $org_file_path)
-            .wrap_err("Could not convert the MR description to ORG")?;
-        org_file_path.clone()
-    } else {
-        orig_desc_file_path.clone()
-    };
+    debug!("host: {}", global_args.host);
+    debug!("project-path: {}", global_args.project_path);
+    trace!("token: {}", global_args.token);
 
-    run_cmd!($editor_command $path_to_edit).wrap_err_with(|| {
+    /*
+     * get the merge request details from the API:
+     */
+    let mr = get_merge_request(args, forge).await.wrap_err_with(|| {
         format!(
-            "Could not open {:?} in editor {}",
-            path_to_edit, editor_command
+            "Failed to fetch the merge request to edit in project {}",
+            global_args.project_path
         )
     })?;
+    debug!("merge request: {} of branch {}", mr.iid, mr.source_branch);
 
-    // convert the edited description back to markdown if necessary:
-    if convert_to_org {
-        run_cmd!(pandoc -f org -t markdown $org_file_path -o $orig_desc_file_path)
-            .wrap_err("Could not convert the edited description back to markdown")?;
-    };
+    let interactive =
+        !(args.is_present(ARG_FILE) || args.is_present(ARG_APPEND) || args.is_present(ARG_PREPEND));
 
-    let mut updated_file = File::open(&orig_desc_file_path).wrap_err_with(|| {
-        format!(
-            "Could not open {:?} with new MR description",
-            orig_desc_file_path
-        )
-    })?;
-    let mut updated_description = String::new();
-    File::read_to_string(&mut updated_file, &mut updated_description)
-        .wrap_err("Failed to read the updated merge request description")?;
+    // templates are only offered for empty descriptions when opening the editor, unless requested explicitly:
+    let template_name = args.value_of(ARG_TEMPLATE);
+    let template = if template_name.is_some() || (interactive && mr.description.trim().is_empty()) {
+        select_template(TemplateKind::MergeRequest, template_name)?
+    } else {
+        None
+    };
+    let description = match template {
+        Some(template) if mr.description.trim().is_empty() => template,
+        Some(template) => format!("{}\n\n{}", mr.description.trim_end(), template),
+        None => mr.description.clone(),
+    };
 
-    // cleanup temp files
-    tmp_dir.close()?;
+    let original_fields = MergeRequestFields::from_mr(&mr);
+    let body = to_buffer(&description, format)?;
+    let buffer = original_fields.render(&body, format.is_org())?;
+    // place the cursor on the first line of the description, below the attributes:
+    let body_line = buffer.lines().count() - body.lines().count() + 1;
 
-    Ok(updated_description)
+    let comments = vec![
+        format!("Editing merge request {}: {}", mr.iid, mr.web_url),
+        format!(
+            "Branch: {} in {} on {}",
+            mr.source_branch, global_args.project_path, global_args.host
+        ),
+        String::from("If nothing is changed, the merge request is not updated."),
+    ];
+    let edited_buffer = if let Some(path) = args.value_of(ARG_FILE) {
+        read_input(path)?
+    } else if !interactive {
+        let edited_body: Vec<&str> = vec![
+            args.value_of(ARG_PREPEND).unwrap_or_default(),
+            &body,
+            args.value_of(ARG_APPEND).unwrap_or_default(),
+        ]
+        .into_iter()
+        .map(|text| text.trim_start_matches('\n').trim_end())
+        .filter(|text| !text.is_empty())
+        .collect();
+        original_fields.render(&format!("{}\n", edited_body.join("\n\n")), format.is_org())?
+    } else {
+        edit_buffer(
+            &buffer_file_name(&mr.iid, format),
+            &buffer,
+            body_line,
+            &comments,
+            global_args.editor_cmd()?,
+        )?
+    };
+    check_changed(&buffer, &edited_buffer)?;
+    check_not_empty(&edited_buffer)?;
+    // the edited attributes are validated by the upload, so the draft is kept if they are invalid:
+    let draft = Draft::create(
+        DraftKind::EditMergeRequest {
+            branch: mr.source_branch.clone(),
+            iid: mr.iid.clone(),
+            original_description: mr.description.clone(),
+            original_fields: original_fields.clone(),
+        },
+        &edited_buffer,
+        format,
+        global_args,
+    )?;
+    draft
+        .upload(upload(
+            &mr.iid,
+            &mr.description,
+            &original_fields,
+            &edited_buffer,
+            format,
+            global_args,
+            forge,
+        ))
+        .await
 }
 
 /**
- * Runs the edit-mr sub-command.
+ * Updates the merge request with the given iid from the given editor buffer.
+ * Only the attributes which differ from the `original_fields` are uploaded. Changes of the description
+ * made on the remote host since the `original_description` was fetched are merged with the local changes.
+ * With JSON output, the iid and URL of the updated merge request are printed.
  */
-pub async fn run<'a>(args: &ArgMatches<'a>, global_args: &GlobalArgs) -> CommandResult {
-    let current_branch = get_branch_name(args)?;
-    let convert_to_org = args.is_present(ARG_USE_ORGMODE);
-
-    debug!("branch: {}", current_branch);
-    debug!("project-path: {}", global_args.project_path);
-    trace!("token: {}", global_args.token);
+pub async fn upload(
+    iid: &str,
+    original_description: &str,
+    original_fields: &MergeRequestFields,
+    buffer: &str,
+    format: &BufferFormat,
+    global_args: &GlobalArgs,
+    forge: &dyn Forge,
+) -> CommandResult {
+    let (fields, new_body) = MergeRequestFields::parse(buffer, format.is_org())?;
+    let mut update = match fields {
+        Some(fields) => fields.to_update(original_fields)?,
+        None => Default::default(),
+    };
 
     /*
-     * get the merge request details from the API:
+     * re-fetch the merge request to detect changes made while editing it:
      */
-    let mr = get_merge_request(
-        &global_args.token,
-        &global_args.project_path,
-        &current_branch,
-    )
-    .await
-    .wrap_err_with(|| {
-        format!(
-            "Failed to fetch the current merge request for project {}, branch {}",
-            global_args.project_path, current_branch
-        )
-    })?;
+    let remote_mr = forge
+        .get_merge_request_by_iid(iid)
+        .await
+        .wrap_err("Failed to re-fetch the merge request before updating it")?;
 
-    let new_description = edit_mr_description(
-        &mr.iid,
-        &mr.description,
-        &global_args.editor_cmd,
-        convert_to_org,
-    )?;
+    // the description is only uploaded if it has been changed locally:
+    if new_body.trim_end() != to_buffer(original_description, format)?.trim_end() {
+        let new_description = from_edited_buffer(original_description, &new_body, format)?;
+
+        update.description = if remote_mr.description == original_description {
+            Some(new_description)
+        } else {
+            Some(merge_remote_changes(
+                original_description,
+                &new_description,
+                &remote_mr.description,
+                &remote_mr.iid,
+                global_args.editor_cmd()?,
+            )?)
+        };
+    }
 
     /*
-     * upload the edited file content as new merge request description:
+     * upload the edited attributes and description:
      */
-    debug!(
-        "updating merge request with new description: '{}'",
-        new_description
-    );
-
-    update_merge_request_desc(
-        &global_args.token,
-        &global_args.project_path,
-        &mr.iid,
-        &new_description,
-    )
-    .await?;
-
-    info!("Updated merge request {} successfully.", mr.iid);
+    debug!("updating merge request with {:?}", update);
+
+    forge
+        .update_merge_request(&remote_mr, &update)
+        .await
+        .wrap_err("Failed to update the merge request")?;
+
+    info!("Updated merge request {} successfully.", remote_mr.iid);
+    if global_args.output == OutputFormat::Json {
+        print_json(&json!({ "iid": remote_mr.iid, "web_url": remote_mr.web_url }))?;
+    }
 
     Ok(())
 }
@@ -0,0 +1,267 @@
+use clap::{App, Arg, ArgMatches, SubCommand};
+use colored::Colorize;
+use eyre::{eyre, Context, Report};
+
+use crate::{
+    cli::{GlobalArgs, ARG_COLUMNS},
+    forges::{Forge, IssueFilter, IssueState, IssueSummary, ISSUE_STATES},
+    output::{print_json, OutputFormat},
+    table::{format_time, print_table, truncate, Cell},
+};
+
+use super::CommandResult;
+
+pub const CMD_IDENTIFIER: &str = "list-issues";
+const CMD_ABOUT: &str = r#"
+Lists the issues of the project, most recently updated first.
+By default, the 20 most recently updated open issues are listed. All given filters must match.
+
+The columns of the table can be chosen via --columns or as 'issue_columns' in the config file. Available columns are: iid, title, state, author, assignees, labels, milestone, due, confidential, updated and url.
+"#;
+
+const ARG_STATE: &str = "state";
+const ARG_LABEL: &str = "label";
+const ARG_ASSIGNEE: &str = "assignee";
+const ARG_MILESTONE: &str = "milestone";
+const ARG_SEARCH: &str = "search";
+const ARG_CONFIDENTIAL: &str = "confidential";
+const ARG_PUBLIC: &str = "public";
+const ARG_DUE_FROM: &str = "due-from";
+const ARG_DUE_UNTIL: &str = "due-until";
+const ARG_LIMIT: &str = "limit";
+
+/// names of all columns which can be shown
+pub const COLUMNS: &[&str] = &[
+    "iid",
+    "title",
+    "state",
+    "author",
+    "assignees",
+    "labels",
+    "milestone",
+    "due",
+    "confidential",
+    "updated",
+    "url",
+];
+
+/// names of the columns shown if none are configured
+pub const DEFAULT_COLUMNS: &[&str] = &["iid", "title", "state", "assignees", "labels", "updated"];
+
+/// maximum number of characters of a title shown in the table
+const MAX_TITLE_WIDTH: usize = 60;
+
+/// validates that the given value is a date in the format 'YYYY-MM-DD'
+fn validate_date(value: String) -> Result<(), String> {
+    let valid = value.len() == 10
+        && value.char_indices().all(|(i, c)| match i {
+            4 | 7 => c == '-',
+            _ => c.is_ascii_digit(),
+        });
+    if valid {
+        Ok(())
+    } else {
+        Err(format!("Invalid date '{}', expected 'YYYY-MM-DD'", value))
+    }
+}
+
+/// returns the clap definition for this sub-command
+pub fn get_subcommand<'a, 'b>() -> App<'a, 'b> {
+    SubCommand::with_name(CMD_IDENTIFIER)
+        .about(CMD_ABOUT)
+        .alias("li")
+        .arg(
+            Arg::with_name(ARG_STATE)
+                .long("state")
+                .short("s")
+                .takes_value(true)
+                .possible_values(ISSUE_STATES)
+                .default_value("opened")
+                .help("Lists only issues in the given state"),
+        )
+        .arg(
+            Arg::with_name(ARG_LABEL)
+                .long("label")
+                .short("l")
+                .takes_value(true)
+                .multiple(true)
+                .number_of_values(1)
+                .help("Lists only issues with the given label. Can be passed multiple times, all labels must be set"),
+        )
+        .arg(
+            Arg::with_name(ARG_ASSIGNEE)
+                .long("assignee")
+                .takes_value(true)
+                .help("Lists only issues assigned to the user with the given username"),
+        )
+        .arg(
+            Arg::with_name(ARG_MILESTONE)
+                .long("milestone")
+                .takes_value(true)
+                .help("Lists only issues of the milestone with the given title"),
+        )
+        .arg(
+            Arg::with_name(ARG_SEARCH)
+                .long("search")
+                .takes_value(true)
+                .help("Lists only issues containing the given text in their title or description"),
+        )
+        .arg(
+            Arg::with_name(ARG_CONFIDENTIAL)
+                .long("confidential")
+                .conflicts_with(ARG_PUBLIC)
+                .help("Lists only confidential issues"),
+        )
+        .arg(
+            Arg::with_name(ARG_PUBLIC)
+                .long("public")
+                .help("Lists only issues which are not confidential"),
+        )
+        .arg(
+            Arg::with_name(ARG_DUE_FROM)
+                .long("due-from")
+                .takes_value(true)
+                .validator(validate_date)
+                .help("Lists only issues due on or after the given date, e.g. '2021-08-01'"),
+        )
+        .arg(
+            Arg::with_name(ARG_DUE_UNTIL)
+                .long("due-until")
+                .takes_value(true)
+                .validator(validate_date)
+                .help("Lists only issues due on or before the given date, e.g. '2021-08-31'"),
+        )
+        .arg(
+            Arg::with_name(ARG_LIMIT)
+                .long("limit")
+                .short("n")
+                .takes_value(true)
+                .default_value("20")
+                .help("The maximum number of issues to list"),
+        )
+        .arg(
+            Arg::with_name(ARG_COLUMNS)
+                .long("columns")
+                .short("c")
+                .takes_value(true)
+                .help("The comma separated columns to show, e.g. 'iid,title,due'. Can also be set as 'issue_columns' in ~/.config/forge/config.toml")
+                .env("FORGE_CLI_ISSUE_COLUMNS"),
+        )
+}
+
+/// returns the filter described by the given arguments
+fn filter_from_args(args: &ArgMatches) -> Result<IssueFilter, Report> {
+    let limit = args.value_of(ARG_LIMIT).unwrap_or_default();
+    let confidential = if args.is_present(ARG_CONFIDENTIAL) {
+        Some(true)
+    } else if args.is_present(ARG_PUBLIC) {
+        Some(false)
+    } else {
+        None
+    };
+
+    Ok(IssueFilter {
+        state: args
+            .value_of(ARG_STATE)
+            .unwrap_or_default()
+            .parse::<IssueState>()?,
+        labels: args
+            .values_of(ARG_LABEL)
+            .map(|labels| labels.map(String::from).collect())
+            .unwrap_or_default(),
+        assignee: args.value_of(ARG_ASSIGNEE).map(String::from),
+        milestone: args.value_of(ARG_MILESTONE).map(String::from),
+        search: args.value_of(ARG_SEARCH).map(String::from),
+        confidential,
+        due_from: args.value_of(ARG_DUE_FROM).map(String::from),
+        due_until: args.value_of(ARG_DUE_UNTIL).map(String::from),
+        limit: limit
+            .parse()
+            .wrap_err_with(|| format!("Invalid limit '{}'", limit))?,
+    })
+}
+
+/// fails if the given columns are empty or contain unknown ones
+fn check_columns(columns: &[String]) -> Result<(), Report> {
+    if columns.is_empty() {
+        return Err(eyre!(
+            "No columns to show, expected some of {}",
+            COLUMNS.join(", ")
+        ));
+    }
+    if let Some(unknown) = columns
+        .iter()
+        .find(|column| !COLUMNS.contains(&column.as_str()))
+    {
+        return Err(eyre!(
+            "Unknown column '{}', expected one of {}",
+            unknown,
+            COLUMNS.join(", ")
+        ));
+    }
+    Ok(())
+}
+
+/// returns the cell of the given column for the given issue
+fn cell(column: &str, issue: &IssueSummary) -> Cell {
+    match column {
+        "iid" => Cell::styled(&issue.iid, |text| text.cyan()),
+        "title" => Cell::new(truncate(&issue.title, MAX_TITLE_WIDTH)),
+        "state" if issue.state == "opened" => Cell::styled(&issue.state, |text| text.green()),
+        "state" => Cell::styled(&issue.state, |text| text.red()),
+        "author" => Cell::new(&issue.author),
+        "assignees" => Cell::new(issue.assignees.join(", ")),
+        "labels" => Cell::styled(issue.labels.join(", "), |text| text.yellow()),
+        "milestone" => Cell::new(issue.milestone.clone().unwrap_or_default()),
+        // due dates may include a time, which is not shown:
+        "due" => Cell::new(
+            issue
+                .due_date
+                .as_ref()
+                .map(|date| date.chars().take(10).collect::<String>())
+                .unwrap_or_default(),
+        ),
+        "confidential" if issue.confidential => Cell::styled("yes", |text| text.magenta()),
+        "confidential" => Cell::new(""),
+        "updated" => Cell::styled(format_time(&issue.updated_at), |text| text.dimmed()),
+        "url" => Cell::styled(&issue.web_url, |text| text.dimmed()),
+        _ => Cell::new(""),
+    }
+}
+
+/**
+ * Runs the list-issues sub-command.
+ */
+pub async fn run<'a>(
+    args: &ArgMatches<'a>,
+    global_args: &GlobalArgs,
+    forge: &dyn Forge,
+) -> CommandResult {
+    let columns = &global_args.issue_columns;
+    // the JSON output always contains all columns:
+    if global_args.output == OutputFormat::Text {
+        check_columns(columns)?;
+    }
+
+    let filter = filter_from_args(args)?;
+    let issues = forge
+        .list_issues(&filter)
+        .await
+        .wrap_err("Failed to list the issues")?;
+
+    match global_args.output {
+        OutputFormat::Json => print_json(&issues)?,
+        OutputFormat::Text if issues.is_empty() => println!("No issues found."),
+        OutputFormat::Text => {
+            let headers: Vec<String> = columns.iter().map(|column| column.to_uppercase()).collect();
+            let headers: Vec<&str> = headers.iter().map(String::as_str).collect();
+            let rows: Vec<Vec<Cell>> = issues
+                .iter()
+                .map(|issue| columns.iter().map(|column| cell(column, issue)).collect())
+                .collect();
+            print_table(&headers, &rows);
+        }
+    }
+
+    Ok(())
+}
@@ -0,0 +1,192 @@
+use clap::{App, Arg, ArgMatches, SubCommand};
+use colored::{ColoredString, Colorize};
+use eyre::{Context, Report};
+
+use crate::{
+    cli::GlobalArgs,
+    forges::{
+        Forge, MergeRequestFilter, MergeRequestState, MergeRequestSummary, MERGE_REQUEST_STATES,
+    },
+    output::{print_json, OutputFormat},
+    table::{format_time, print_table, truncate, Cell},
+};
+
+use super::CommandResult;
+
+pub const CMD_IDENTIFIER: &str = "list-mrs";
+const CMD_ABOUT: &str = r#"
+Lists the merge requests of the project, most recently updated first.
+By default, the 20 most recently updated open merge requests are listed. All given filters must match.
+"#;
+
+const ARG_STATE: &str = "state";
+const ARG_AUTHOR: &str = "author";
+const ARG_ASSIGNEE: &str = "assignee";
+const ARG_REVIEWER: &str = "reviewer";
+const ARG_LABEL: &str = "label";
+const ARG_TARGET: &str = "target";
+const ARG_DRAFT: &str = "draft";
+const ARG_NO_DRAFT: &str = "no-draft";
+const ARG_LIMIT: &str = "limit";
+
+/// maximum number of characters of a title shown in the table
+const MAX_TITLE_WIDTH: usize = 60;
+
+/// returns the clap definition for this sub-command
+pub fn get_subcommand<'a, 'b>() -> App<'a, 'b> {
+    SubCommand::with_name(CMD_IDENTIFIER)
+        .about(CMD_ABOUT)
+        .alias("lm")
+        .arg(
+            Arg::with_name(ARG_STATE)
+                .long("state")
+                .short("s")
+                .takes_value(true)
+                .possible_values(MERGE_REQUEST_STATES)
+                .default_value("opened")
+                .help("Lists only merge requests in the given state"),
+        )
+        .arg(
+            Arg::with_name(ARG_AUTHOR)
+                .long("author")
+                .takes_value(true)
+                .help("Lists only merge requests created by the user with the given username"),
+        )
+        .arg(
+            Arg::with_name(ARG_ASSIGNEE)
+                .long("assignee")
+                .takes_value(true)
+                .help("Lists only merge requests assigned to the user with the given username"),
+        )
+        .arg(
+            Arg::with_name(ARG_REVIEWER)
+                .long("reviewer")
+                .takes_value(true)
+                .help("Lists only merge requests to be reviewed by the user with the given username"),
+        )
+        .arg(
+            Arg::with_name(ARG_LABEL)
+                .long("label")
+                .short("l")
+                .takes_value(true)
+                .multiple(true)
+                .number_of_values(1)
+                .help("Lists only merge requests with the given label. Can be passed multiple times, all labels must be set"),
+        )
+        .arg(
+            Arg::with_name(ARG_TARGET)
+                .long("target")
+                .takes_value(true)
+                .help("Lists only merge requests into the given target branch"),
+        )
+        .arg(
+            Arg::with_name(ARG_DRAFT)
+                .long("draft")
+                .conflicts_with(ARG_NO_DRAFT)
+                .help("Lists only draft merge requests"),
+        )
+        .arg(
+            Arg::with_name(ARG_NO_DRAFT)
+                .long("no-draft")
+                .help("Lists only merge requests which are ready, i.e. not drafts"),
+        )
+        .arg(
+            Arg::with_name(ARG_LIMIT)
+                .long("limit")
+                .short("n")
+                .takes_value(true)
+                .default_value("20")
+                .help("The maximum number of merge requests to list"),
+        )
+}
+
+/// returns the filter described by the given arguments
+fn filter_from_args(args: &ArgMatches) -> Result<MergeRequestFilter, Report> {
+    let limit = args.value_of(ARG_LIMIT).unwrap_or_default();
+    let draft = if args.is_present(ARG_DRAFT) {
+        Some(true)
+    } else if args.is_present(ARG_NO_DRAFT) {
+        Some(false)
+    } else {
+        None
+    };
+
+    Ok(MergeRequestFilter {
+        state: args
+            .value_of(ARG_STATE)
+            .unwrap_or_default()
+            .parse::<MergeRequestState>()?,
+        author: args.value_of(ARG_AUTHOR).map(String::from),
+        assignee: args.value_of(ARG_ASSIGNEE).map(String::from),
+        reviewer: args.value_of(ARG_REVIEWER).map(String::from),
+        labels: args
+            .values_of(ARG_LABEL)
+            .map(|labels| labels.map(String::from).collect())
+            .unwrap_or_default(),
+        target_branch: args.value_of(ARG_TARGET).map(String::from),
+        draft,
+        limit: limit
+            .parse()
+            .wrap_err_with(|| format!("Invalid limit '{}'", limit))?,
+    })
+}
+
+/// returns the style of the given pipeline status, coloured by its outcome
+fn pipeline_status_style(status: &str) -> fn(String) -> ColoredString {
+    match status {
+        "success" => |text| text.green(),
+        "failed" => |text| text.red(),
+        "canceled" | "skipped" | "manual" | "-" => |text| text.dimmed(),
+        _ => |text| text.yellow(),
+    }
+}
+
+/// prints the given merge requests as table, one merge request per line
+fn print_merge_requests(mrs: &[MergeRequestSummary]) {
+    let rows: Vec<Vec<Cell>> = mrs
+        .iter()
+        .map(|mr| {
+            let title = if mr.draft {
+                Cell::styled(
+                    truncate(&format!("Draft: {}", mr.title), MAX_TITLE_WIDTH),
+                    |text| text.dimmed(),
+                )
+            } else {
+                Cell::new(truncate(&mr.title, MAX_TITLE_WIDTH))
+            };
+            let pipeline_status = mr.pipeline_status.as_deref().unwrap_or("-");
+            vec![
+                Cell::styled(&mr.iid, |text| text.cyan()),
+                title,
+                Cell::new(&mr.author),
+                Cell::styled(pipeline_status, pipeline_status_style(pipeline_status)),
+                Cell::styled(format_time(&mr.updated_at), |text| text.dimmed()),
+            ]
+        })
+        .collect();
+
+    print_table(&["IID", "TITLE", "AUTHOR", "PIPELINE", "UPDATED"], &rows);
+}
+
+/**
+ * Runs the list-mrs sub-command.
+ */
+pub async fn run<'a>(
+    args: &ArgMatches<'a>,
+    global_args: &GlobalArgs,
+    forge: &dyn Forge,
+) -> CommandResult {
+    let filter = filter_from_args(args)?;
+    let mrs = forge
+        .list_merge_requests(&filter)
+        .await
+        .wrap_err("Failed to list the merge requests")?;
+
+    match global_args.output {
+        OutputFormat::Json => print_json(&mrs)?,
+        OutputFormat::Text if mrs.is_empty() => println!("No merge requests found."),
+        OutputFormat::Text => print_merge_requests(&mrs),
+    }
+
+    Ok(())
+}
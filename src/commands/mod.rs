@@ -1,26 +1,65 @@
 use clap::ArgMatches;
 use eyre::{eyre, Report};
 
-use crate::cli::GlobalArgs;
+use crate::{cli::GlobalArgs, forges::get_forge};
 
+pub mod config;
 pub mod create_issue;
+pub mod create_mr;
+pub mod drafts;
+pub mod edit_issue;
 pub mod edit_merge_request;
+pub mod list_issues;
+pub mod list_mrs;
 pub mod open_mr;
+pub mod show_issue;
+pub mod show_mr;
 
 pub type CommandResult = Result<(), Report>;
 
 /// runs the appropriate command based on the provided process arguments
 pub async fn run_command<'a>(cli_args: &ArgMatches<'a>) -> CommandResult {
-    let global_args = GlobalArgs::from_cli_args(&cli_args)?;
+    // the config command must work even if the global arguments are incomplete:
+    if let (config::CMD_IDENTIFIER, Some(cmd_args)) = cli_args.subcommand() {
+        return config::run(cli_args, cmd_args);
+    }
+    // listing and discarding drafts must work without a project, resuming resolves the global arguments itself:
+    if let (drafts::CMD_IDENTIFIER, Some(cmd_args)) = cli_args.subcommand() {
+        return drafts::run(cli_args, cmd_args).await;
+    }
+
+    let global_args = GlobalArgs::from_cli_args(cli_args)?;
+    let forge = get_forge(&global_args)?;
+    let forge = forge.as_ref();
 
     match cli_args.subcommand() {
         (edit_merge_request::CMD_IDENTIFIER, Some(cmd_args)) => {
-            edit_merge_request::run(cmd_args, &global_args).await
+            edit_merge_request::run(cmd_args, &global_args, forge).await
         }
         (create_issue::CMD_IDENTIFIER, Some(cmd_args)) => {
-            create_issue::run(cmd_args, &global_args).await
+            create_issue::run(cmd_args, &global_args, forge).await
+        }
+        (edit_issue::CMD_IDENTIFIER, Some(cmd_args)) => {
+            edit_issue::run(cmd_args, &global_args, forge).await
+        }
+        (create_mr::CMD_IDENTIFIER, Some(cmd_args)) => {
+            create_mr::run(cmd_args, &global_args, forge).await
+        }
+        (open_mr::CMD_IDENTIFIER, Some(cmd_args)) => {
+            open_mr::run(cmd_args, &global_args, forge).await
+        }
+        (list_mrs::CMD_IDENTIFIER, Some(cmd_args)) => {
+            list_mrs::run(cmd_args, &global_args, forge).await
+        }
+        (show_mr::CMD_IDENTIFIER, Some(cmd_args)) => {
+            show_mr::run(cmd_args, &global_args, forge).await
+        }
+        (show_issue::CMD_IDENTIFIER, Some(cmd_args)) => {
+            show_issue::run(cmd_args, &global_args, forge).await
+        }
+        (list_issues::CMD_IDENTIFIER, Some(cmd_args)) => {
+            list_issues::run(cmd_args, &global_args, forge).await
         }
-        (open_mr::CMD_IDENTIFIER, Some(cmd_args)) => open_mr::run(cmd_args, &global_args).await,
         ("", _) => Err(eyre!("Missing command. Use --help for more info")),
         // should never be called thanks to `clap`s own validation:
         (cmd, _) => Err(eyre!("Invalid or unknown command: {}", cmd.to_string())),
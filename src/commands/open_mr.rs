@@ -1,10 +1,12 @@
 use clap::{App, ArgMatches, SubCommand};
-use open;
+
+use serde_json::json;
 
 use crate::{
-    cli::{arg_branch, GlobalArgs},
-    get_branch_name,
-    queries::gitlab_get_mr::get_merge_request,
+    cli::{arg_branch, arg_mr_iid, arg_mr_state, GlobalArgs},
+    forges::Forge,
+    get_merge_request,
+    output::{print_json, OutputFormat},
 };
 
 use super::CommandResult;
@@ -12,7 +14,9 @@ use super::CommandResult;
 pub const CMD_IDENTIFIER: &str = "open-mr";
 const CMD_ABOUT: &str = r#"
 Opens the MR of the currently checked out branch in your default browser.
-The branch can be overwritten using the --branch flag.
+The branch can be overwritten using the --branch flag, the MR can also be chosen by its iid via --iid.
+If the branch has several MRs, open ones are preferred, otherwise --state chooses between them.
+With '--output json', the iid and URL of the MR are printed as well.
 "#;
 
 /// returns the clap definition for this sub-command
@@ -21,13 +25,21 @@ pub fn get_subcommand<'a, 'b>() -> App<'a, 'b> {
         .about(CMD_ABOUT)
         .alias("o")
         .arg(arg_branch())
+        .arg(arg_mr_iid())
+        .arg(arg_mr_state())
 }
 
-pub async fn run<'a>(args: &ArgMatches<'a>, global_args: &GlobalArgs) -> CommandResult {
-    let branch_name = get_branch_name(args)?;
-    let mr = get_merge_request(&global_args.token, &global_args.project_path, &branch_name).await?;
+pub async fn run<'a>(
+    args: &ArgMatches<'a>,
+    global_args: &GlobalArgs,
+    forge: &dyn Forge,
+) -> CommandResult {
+    let mr = get_merge_request(args, forge).await?;
 
-    open::that(mr.web_url)?;
+    open::that(&mr.web_url)?;
+    if global_args.output == OutputFormat::Json {
+        print_json(&json!({ "iid": mr.iid, "web_url": mr.web_url }))?;
+    }
 
     Ok(())
 }
@@ -0,0 +1,81 @@
+use clap::{App, Arg, ArgMatches, SubCommand};
+use colored::Colorize;
+use eyre::Context;
+
+use crate::{
+    cli::GlobalArgs,
+    forges::Forge,
+    output::{print_json, OutputFormat},
+    render::{render_discussions, render_header, render_markdown, render_state},
+};
+
+use super::CommandResult;
+
+pub const CMD_IDENTIFIER: &str = "show-issue";
+const CMD_ABOUT: &str = r#"
+Shows the issue with the given iid.
+Prints its title, attributes, the description rendered for the terminal and the discussion.
+"#;
+
+const ARG_IID: &str = "iid";
+
+/// returns the clap definition for this sub-command
+pub fn get_subcommand<'a, 'b>() -> App<'a, 'b> {
+    SubCommand::with_name(CMD_IDENTIFIER)
+        .about(CMD_ABOUT)
+        .alias("si")
+        .arg(
+            Arg::with_name(ARG_IID)
+                .required(true)
+                .help("The iid of the issue to show, e.g. '12'"),
+        )
+}
+
+/**
+ * Runs the show-issue sub-command.
+ */
+pub async fn run<'a>(
+    args: &ArgMatches<'a>,
+    global_args: &GlobalArgs,
+    forge: &dyn Forge,
+) -> CommandResult {
+    let iid = args.value_of(ARG_IID).unwrap_or_default();
+    let issue = forge
+        .get_issue(iid)
+        .await
+        .wrap_err_with(|| format!("Failed to fetch issue {}", iid))?;
+    let discussions = forge
+        .get_issue_discussions(iid)
+        .await
+        .wrap_err("Failed to fetch the discussion of the issue")?;
+
+    if global_args.output == OutputFormat::Json {
+        let mut json = serde_json::to_value(&issue)?;
+        json["discussions"] = serde_json::to_value(&discussions)?;
+        return print_json(&json);
+    }
+
+    let summary = vec![
+        format!("Issue {}", issue.iid).cyan().to_string(),
+        render_state(&issue.state),
+        format!("by {}", issue.author),
+    ];
+    let attributes = [
+        ("Labels", issue.labels.join(", ")),
+        ("Assignees", issue.assignees.join(", ")),
+        ("Milestone", issue.milestone.clone().unwrap_or_default()),
+    ];
+
+    println!(
+        "{}\n",
+        render_header(&issue.title, &summary, &attributes, &issue.web_url)
+    );
+    if !issue.description.trim().is_empty() {
+        println!("{}\n", render_markdown(issue.description.trim_end()));
+    }
+    if !discussions.is_empty() {
+        print!("{}", render_discussions(&discussions));
+    }
+
+    Ok(())
+}
@@ -0,0 +1,85 @@
+use clap::{App, Arg, ArgMatches, SubCommand};
+use colored::Colorize;
+use eyre::Context;
+
+use crate::{
+    cli::{arg_branch, arg_mr_state, GlobalArgs, ARG_BRANCH, ARG_IID},
+    forges::Forge,
+    get_merge_request,
+    output::{print_json, OutputFormat},
+    render::{render_discussions, render_header, render_markdown, render_state},
+};
+
+use super::CommandResult;
+
+pub const CMD_IDENTIFIER: &str = "show-mr";
+const CMD_ABOUT: &str = r#"
+Shows the merge request with the given iid, or the one of the currently checked out branch (if no iid or branch specified via -b).
+If the branch has several merge requests, open ones are preferred, otherwise --state chooses between them.
+Prints its title, attributes, the description rendered for the terminal and the discussion.
+"#;
+
+/// returns the clap definition for this sub-command
+pub fn get_subcommand<'a, 'b>() -> App<'a, 'b> {
+    SubCommand::with_name(CMD_IDENTIFIER)
+        .about(CMD_ABOUT)
+        .alias("sm")
+        .arg(
+            Arg::with_name(ARG_IID)
+                .required(false)
+                .conflicts_with(ARG_BRANCH)
+                .help("The iid of the merge request to show, e.g. '12'"),
+        )
+        .arg(arg_branch())
+        .arg(arg_mr_state())
+}
+
+/**
+ * Runs the show-mr sub-command.
+ */
+pub async fn run<'a>(
+    args: &ArgMatches<'a>,
+    global_args: &GlobalArgs,
+    forge: &dyn Forge,
+) -> CommandResult {
+    let mr = get_merge_request(args, forge).await?;
+    let discussions = forge
+        .get_merge_request_discussions(&mr.iid)
+        .await
+        .wrap_err("Failed to fetch the discussion of the merge request")?;
+
+    if global_args.output == OutputFormat::Json {
+        let mut json = serde_json::to_value(&mr)?;
+        json["discussions"] = serde_json::to_value(&discussions)?;
+        return print_json(&json);
+    }
+
+    let mut summary = vec![
+        format!("Merge request {}", mr.iid).cyan().to_string(),
+        render_state(&mr.state),
+        format!("by {}", mr.author),
+        format!("{} → {}", mr.source_branch, mr.target_branch),
+    ];
+    if mr.draft {
+        summary.push("draft".yellow().to_string());
+    }
+    let attributes = [
+        ("Labels", mr.labels.join(", ")),
+        ("Assignees", mr.assignees.join(", ")),
+        ("Reviewers", mr.reviewers.join(", ")),
+        ("Milestone", mr.milestone.clone().unwrap_or_default()),
+    ];
+
+    println!(
+        "{}\n",
+        render_header(&mr.title, &summary, &attributes, &mr.web_url)
+    );
+    if !mr.description.trim().is_empty() {
+        println!("{}\n", render_markdown(mr.description.trim_end()));
+    }
+    if !discussions.is_empty() {
+        print!("{}", render_discussions(&discussions));
+    }
+
+    Ok(())
+}
@@ -0,0 +1,250 @@
+use eyre::{eyre, Context, Report};
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+/// name of the repository-local config file, expected in the root directory of the git repository
+pub const REPO_CONFIG_FILE_NAME: &str = ".forge.toml";
+
+/**
+ * Settings which can be set both at the top level of a config file and per host profile,
+ * e.g. in a `[hosts."gitlab.example.com"]` section.
+ */
+#[derive(Debug, Default, Deserialize)]
+pub struct Profile {
+    /// kind of the remote forge, e.g. 'gitlab'
+    pub forge: Option<String>,
+    /// name of the environment variable containing the authentication token
+    pub token_env: Option<String>,
+    /// shell command printing the authentication token, e.g. 'pass show gitlab'
+    pub token_command: Option<String>,
+    /// other source of the authentication token, either 'git-credential' or 'netrc'
+    pub token_source: Option<String>,
+    /// command of the editor to use, e.g. 'code --wait', see `editor::edit_file`
+    pub editor: Option<String>,
+    /// whether issues and merge requests are edited in ORG mode by default
+    pub org_mode: Option<bool>,
+    /// format issues and merge requests are edited in by default, e.g. 'rst'
+    pub format: Option<String>,
+    /// converter used in ORG mode, either 'builtin' or 'pandoc'
+    pub converter: Option<String>,
+    /// labels added to newly created issues
+    pub labels: Option<Vec<String>>,
+    /// columns shown by list-issues, e.g. ['iid', 'title', 'due']
+    pub issue_columns: Option<Vec<String>>,
+}
+
+/**
+ * The content of a single config file.
+ * All entries are optional; values passed as CLI arguments or environment variables take precedence.
+ */
+#[derive(Debug, Default, Deserialize)]
+pub struct ConfigFile {
+    /// base URL of the remote forge, e.g. 'https://gitlab.example.com'
+    pub host: Option<String>,
+    /// path of the project on the remote forge, e.g. 'mygroup/myproject'
+    pub project_path: Option<String>,
+    /// name of the git remote used to derive the project path, host and forge
+    pub remote: Option<String>,
+    /// path of the upstream project if the project is a fork, e.g. 'mygroup/myproject'
+    pub upstream: Option<String>,
+    #[serde(flatten)]
+    pub defaults: Profile,
+    /// profiles per host name, e.g. 'gitlab.example.com'
+    #[serde(default)]
+    pub hosts: HashMap<String, Profile>,
+    /// additional formats by name, e.g. 'asciidoc'
+    #[serde(default)]
+    pub formats: HashMap<String, FormatConfig>,
+}
+
+/**
+ * A format converted from and to markdown by external commands, e.g. in a `[formats.asciidoc]` section.
+ * Both commands read the text from stdin and write the converted text to stdout.
+ */
+#[derive(Debug, Clone, Deserialize)]
+pub struct FormatConfig {
+    /// file extension of the editor buffer, defaults to the name of the format
+    pub extension: Option<String>,
+    /// shell command converting markdown to this format, e.g. 'pandoc -f gfm -t asciidoc'
+    pub from_markdown: String,
+    /// shell command converting this format to markdown
+    pub to_markdown: String,
+}
+
+/**
+ * The scopes config files can be loaded from, ordered by precedence.
+ */
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ConfigScope {
+    /// the `.forge.toml` file in the root of the current git repository
+    Repository,
+    /// the user's config file at `~/.config/forge/config.toml`
+    User,
+}
+
+/**
+ * A config file loaded from disk.
+ */
+#[derive(Debug)]
+pub struct ConfigLayer {
+    pub scope: ConfigScope,
+    pub path: PathBuf,
+    pub file: ConfigFile,
+}
+
+/// returns the path of the user's config file, if a config directory is available on this platform
+pub fn user_config_file_path() -> Option<PathBuf> {
+    dirs::config_dir().map(|dir| dir.join("forge").join("config.toml"))
+}
+
+/// returns the root directory of the current git repository, if the current directory is inside one
+pub fn repo_root() -> Option<PathBuf> {
+    // not being inside a git repository is not an error, so we don't use `run_fun!`, which would log it:
+    let output = Command::new("git")
+        .args(["rev-parse", "--show-toplevel"])
+        .output()
+        .ok()
+        .filter(|output| output.status.success())?;
+    let root = String::from_utf8_lossy(&output.stdout).trim().to_string();
+
+    Some(PathBuf::from(root))
+}
+
+/// returns the path of the repository-local config file, if the current directory is inside a git repository
+pub fn repo_config_file_path() -> Option<PathBuf> {
+    repo_root().map(|root| root.join(REPO_CONFIG_FILE_NAME))
+}
+
+/**
+ * Loads the config file at the given path. Returns `None` if the file does not exist.
+ */
+fn load_config_file(path: &Path) -> Result<Option<ConfigFile>, Report> {
+    if !path.exists() {
+        return Ok(None);
+    }
+
+    let content = fs::read_to_string(path)
+        .wrap_err_with(|| format!("Could not read config file {:?}", path))?;
+    let file =
+        toml::from_str(&content).wrap_err_with(|| format!("Invalid config file {:?}", path))?;
+    Ok(Some(file))
+}
+
+/**
+ * Fails if the given user config file contains settings which are specific to a single repository
+ * and are therefore only read from the repository-local config file.
+ */
+fn check_repository_settings(file: &ConfigFile, path: &Path) -> Result<(), Report> {
+    let repository_settings = [
+        ("project_path", &file.project_path),
+        ("upstream", &file.upstream),
+    ];
+    match repository_settings
+        .iter()
+        .find(|(_, value)| value.is_some())
+    {
+        Some((key, _)) => Err(eyre!(
+            "'{}' in {:?} is specific to a repository and can only be set in its {}",
+            key,
+            path,
+            REPO_CONFIG_FILE_NAME
+        )),
+        None => Ok(()),
+    }
+}
+
+/**
+ * Fails if the given repository-local config file contains settings which run commands or decide where the token
+ * is sent to. A cloned repository must not be able to run code or obtain the token, so these settings
+ * are only read from the user's config file.
+ */
+fn check_user_settings(file: &ConfigFile, path: &Path) -> Result<(), Report> {
+    let profiles = std::iter::once(&file.defaults).chain(file.hosts.values());
+    let mut user_settings = vec![
+        ("host", file.host.is_some()),
+        ("formats", !file.formats.is_empty()),
+    ];
+    for profile in profiles {
+        user_settings.extend([
+            ("token_command", profile.token_command.is_some()),
+            ("token_env", profile.token_env.is_some()),
+            ("token_source", profile.token_source.is_some()),
+            ("editor", profile.editor.is_some()),
+        ]);
+    }
+    match user_settings.iter().find(|(_, is_set)| *is_set) {
+        Some((key, _)) => Err(eyre!(
+            "'{}' in {:?} is not allowed in a repository and can only be set in the user config file",
+            key,
+            path
+        )),
+        None => Ok(()),
+    }
+}
+
+/**
+ * Loads all existing config files, ordered by precedence: the repository-local config file comes first,
+ * followed by the user's config file.
+ */
+pub fn load_config_layers() -> Result<Vec<ConfigLayer>, Report> {
+    let paths = vec![
+        (ConfigScope::Repository, repo_config_file_path()),
+        (ConfigScope::User, user_config_file_path()),
+    ];
+
+    let mut layers = vec![];
+    for (scope, path) in paths {
+        if let Some(path) = path {
+            if let Some(file) = load_config_file(&path)? {
+                match scope {
+                    ConfigScope::Repository => check_user_settings(&file, &path)?,
+                    ConfigScope::User => check_repository_settings(&file, &path)?,
+                }
+                layers.push(ConfigLayer { scope, path, file });
+            }
+        }
+    }
+
+    Ok(layers)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(content: &str) -> ConfigFile {
+        toml::from_str(content).unwrap()
+    }
+
+    #[test]
+    fn rejects_commands_and_hosts_in_repositories() {
+        let path = Path::new(REPO_CONFIG_FILE_NAME);
+        for content in [
+            "host = \"https://evil.example.com\"",
+            "token_command = \"curl evil.example.com\"",
+            "editor = \"sh -c 'rm -rf ~'\"",
+            "[hosts.\"gitlab.com\"]\ntoken_env = \"OTHER_TOKEN\"",
+            "[formats.x]\nfrom_markdown = \"cat\"\nto_markdown = \"cat\"",
+        ] {
+            assert!(
+                check_user_settings(&parse(content), path).is_err(),
+                "{}",
+                content
+            );
+        }
+        let allowed =
+            "project_path = \"g/p\"\nlabels = [\"bug\"]\n[hosts.\"gitlab.com\"]\nformat = \"org\"";
+        assert!(check_user_settings(&parse(allowed), path).is_ok());
+    }
+
+    #[test]
+    fn rejects_repository_settings_in_user_config() {
+        let path = Path::new("config.toml");
+        assert!(check_repository_settings(&parse("project_path = \"g/p\""), path).is_err());
+        assert!(check_repository_settings(&parse("upstream = \"g/p\""), path).is_err());
+        assert!(check_repository_settings(&parse("host = \"https://x.com\""), path).is_ok());
+    }
+}
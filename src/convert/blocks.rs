@@ -0,0 +1,251 @@
+/*!
+ * Block-wise conversion of markdown to ORG, which allows restoring the original markdown of all blocks
+ * the user did not change while editing the ORG text.
+ *
+ * Converting a whole description from markdown to ORG and back rewrites parts of it, e.g. list markers,
+ * line wrapping or HTML comments. To avoid such noise, descriptions are split into blocks separated by
+ * empty lines, which are converted one by one. After editing, unchanged blocks are mapped back to their
+ * original markdown and only new or edited blocks are converted back.
+ *
+ * This is only done with the built-in ORG converter: it converts line by line, so a block converts the same
+ * on its own as within the whole text. External converters like pandoc would have to be run once per block,
+ * and blocks like loose lists, numbered lists or link references depend on the surrounding blocks.
+ */
+use super::org::{fence_marker, markdown_to_org, org_to_markdown};
+
+/// the syntax of a text to be split into blocks
+#[derive(Clone, Copy)]
+enum Syntax {
+    Markdown,
+    Org,
+}
+
+/**
+ * A block of text together with the whitespace separating it from the next block.
+ */
+struct Block<'a> {
+    text: &'a str,
+    separator: &'a str,
+}
+
+/**
+ * Returns the marker closing the multi-line construct starting at the given line, if any,
+ * e.g. '```' for a fenced code block or '#+END_SRC' for an ORG source block.
+ * Such constructs may contain empty lines, which do not separate blocks.
+ */
+fn opened_construct(line: &str, syntax: Syntax) -> Option<String> {
+    let trimmed = line.trim_start();
+    match syntax {
+        Syntax::Markdown => fence_marker(trimmed).or_else(|| {
+            trimmed
+                .find("<!--")
+                .filter(|start| !trimmed[*start..].contains("-->"))
+                .map(|_| String::from("-->"))
+        }),
+        Syntax::Org => trimmed
+            .to_uppercase()
+            .strip_prefix("#+BEGIN_")
+            .and_then(|rest| rest.split_whitespace().next())
+            .map(|kind| format!("#+END_{}", kind)),
+    }
+}
+
+/// returns whether the given line closes the construct with the given closing marker
+fn closes_construct(line: &str, marker: &str) -> bool {
+    let trimmed = line.trim_start();
+    if marker == "-->" {
+        trimmed.contains(marker)
+    } else if marker.starts_with("#+") {
+        trimmed.to_uppercase().starts_with(marker)
+    } else {
+        trimmed.starts_with(marker) && trimmed.trim_start_matches(&marker[..1]).trim().is_empty()
+    }
+}
+
+/**
+ * Splits the given text into blocks separated by empty lines. Returns the leading whitespace of
+ * the text and its blocks.
+ */
+fn split_blocks(text: &str, syntax: Syntax) -> (&str, Vec<Block<'_>>) {
+    let content = text.trim_start();
+    let prefix = &text[..text.len() - content.len()];
+
+    let mut blocks = vec![];
+    let mut block_start = 0;
+    let mut offset = 0;
+    let mut open: Option<String> = None;
+    let mut previous_blank = false;
+
+    for line in content.split_inclusive('\n') {
+        let blank = line.trim().is_empty();
+        if open.is_none() && !blank && previous_blank {
+            // a new block starts, the previous one ends at its last non-blank line:
+            let previous = &content[block_start..offset];
+            let text = previous.trim_end();
+            blocks.push(Block {
+                text,
+                separator: &previous[text.len()..],
+            });
+            block_start = offset;
+        }
+        open = match open {
+            Some(marker) if closes_construct(line, &marker) => None,
+            Some(marker) => Some(marker),
+            None => opened_construct(line, syntax),
+        };
+        previous_blank = blank;
+        offset += line.len();
+    }
+
+    let last = &content[block_start..];
+    if !last.trim().is_empty() {
+        let text = last.trim_end();
+        blocks.push(Block {
+            text,
+            separator: &last[text.len()..],
+        });
+    }
+
+    (prefix, blocks)
+}
+
+/// returns whether the given blocks are equal, ignoring trailing whitespace
+fn same_block(a: &str, b: &str) -> bool {
+    a.lines()
+        .map(str::trim_end)
+        .eq(b.lines().map(str::trim_end))
+}
+
+/// converts a single markdown block to ORG, without trailing whitespace
+fn block_to_org(block: &str) -> String {
+    markdown_to_org(&format!("{}\n", block))
+        .trim_end()
+        .to_string()
+}
+
+/**
+ * Converts the given markdown text to ORG block by block.
+ */
+pub fn to_org(markdown: &str) -> String {
+    let (_, blocks) = split_blocks(markdown, Syntax::Markdown);
+    let converted: Vec<String> = blocks
+        .iter()
+        .map(|block| block_to_org(block.text))
+        .collect();
+
+    if converted.is_empty() {
+        String::new()
+    } else {
+        format!("{}\n", converted.join("\n\n"))
+    }
+}
+
+/**
+ * Returns the markdown text of the given edited ORG text, which has been created from the `original`
+ * markdown text using `to_org`. Blocks which have not been changed are restored from the original text,
+ * all other blocks are converted to markdown.
+ */
+pub fn restore_unchanged(original: &str, edited: &str) -> String {
+    let (prefix, original_blocks) = split_blocks(original, Syntax::Markdown);
+    let original_org: Vec<String> = original_blocks
+        .iter()
+        .map(|block| block_to_org(block.text))
+        .collect();
+    let (_, edited_blocks) = split_blocks(edited, Syntax::Org);
+
+    /*
+     * find the longest common subsequence of the original and edited ORG blocks,
+     * lengths[i][j] is the length of the LCS of original_org[i..] and edited_blocks[j..]:
+     */
+    let (n, m) = (original_org.len(), edited_blocks.len());
+    let mut lengths = vec![vec![0; m + 1]; n + 1];
+    for i in (0..n).rev() {
+        for j in (0..m).rev() {
+            lengths[i][j] = if same_block(&original_org[i], edited_blocks[j].text) {
+                lengths[i + 1][j + 1] + 1
+            } else {
+                lengths[i + 1][j].max(lengths[i][j + 1])
+            };
+        }
+    }
+
+    // the markdown blocks of the result, with their original separator if restored:
+    let mut markdown: Vec<(String, Option<&str>)> = vec![];
+    let (mut i, mut j) = (0, 0);
+    while j < m {
+        if i < n && same_block(&original_org[i], edited_blocks[j].text) {
+            markdown.push((
+                original_blocks[i].text.to_string(),
+                Some(original_blocks[i].separator),
+            ));
+            i += 1;
+            j += 1;
+        } else if i < n && lengths[i + 1][j] >= lengths[i][j + 1] {
+            // the original block has been removed or changed
+            i += 1;
+        } else {
+            let converted = org_to_markdown(&format!("{}\n", edited_blocks[j].text));
+            markdown.push((converted.trim_end().to_string(), None));
+            j += 1;
+        }
+    }
+
+    // the trailing whitespace of the original text is kept, e.g. a missing trailing newline:
+    let trailing = match &original[original.trim_end().len()..] {
+        "" if original.is_empty() => "\n",
+        trailing => trailing,
+    };
+    let mut result = prefix.to_string();
+    for (index, (text, separator)) in markdown.iter().enumerate() {
+        result.push_str(text);
+        result.push_str(match separator {
+            _ if index + 1 == markdown.len() => trailing,
+            Some(separator) if separator.matches('\n').count() >= 2 => separator,
+            _ => "\n\n",
+        });
+    }
+
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_unchanged(markdown: &str) {
+        assert_eq!(restore_unchanged(markdown, &to_org(markdown)), markdown);
+    }
+
+    #[test]
+    fn restores_unchanged_text_verbatim() {
+        assert_unchanged("Text  with   odd\tspacing\n\n* star list\n* items\n");
+        assert_unchanged("- loose\n\n- list\n\n- items\n");
+        assert_unchanged("3. starts\n\n4. at three\n");
+        assert_unchanged("See [the docs][docs].\n\n[docs]: https://example.com\n");
+        assert_unchanged("<!-- a comment -->\n\nText\n");
+        assert_unchanged("```\nfirst\n\nsecond\n```\n\nAfter\n");
+    }
+
+    #[test]
+    fn converts_only_edited_blocks() {
+        let original = "* first  block\n\nSecond block\n";
+        let edited = to_org(original).replace("Second block", "Changed *block*");
+        assert_eq!(
+            restore_unchanged(original, &edited),
+            "* first  block\n\nChanged **block**\n"
+        );
+    }
+
+    #[test]
+    fn drops_removed_blocks() {
+        let original = "One\n\nTwo\n\nThree\n";
+        let edited = to_org(original).replace("Two\n\n", "");
+        assert_eq!(restore_unchanged(original, &edited), "One\n\nThree\n");
+    }
+
+    #[test]
+    fn converts_empty_text() {
+        assert_eq!(to_org(""), "");
+        assert_eq!(restore_unchanged("", ""), "");
+    }
+}
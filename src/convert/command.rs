@@ -0,0 +1,42 @@
+/*!
+ * Conversion using external commands configured in the config file.
+ */
+use eyre::{eyre, Context, Report};
+use std::io::Write;
+use std::process::{Command, Stdio};
+
+/**
+ * Runs the given command in a shell, passing the given text on stdin, and returns its output.
+ */
+pub fn convert_with_command(text: &str, cmd: &str) -> Result<String, Report> {
+    let mut child = Command::new("sh")
+        .args(["-c", cmd])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::inherit())
+        .spawn()
+        .wrap_err_with(|| format!("Failed to run converter command '{}'", cmd))?;
+
+    // the text is written from a separate thread, so large outputs can't block the command:
+    let mut stdin = child.stdin.take().unwrap();
+    let input = text.to_string();
+    let writer = std::thread::spawn(move || stdin.write_all(input.as_bytes()));
+
+    let output = child
+        .wait_with_output()
+        .wrap_err_with(|| format!("Failed to run converter command '{}'", cmd))?;
+    writer
+        .join()
+        .map_err(|_| eyre!("Failed to pass the text to converter command '{}'", cmd))?
+        .wrap_err_with(|| format!("Failed to pass the text to converter command '{}'", cmd))?;
+
+    if !output.status.success() {
+        return Err(eyre!(
+            "Converter command '{}' failed with {}",
+            cmd,
+            output.status
+        ));
+    }
+    String::from_utf8(output.stdout)
+        .wrap_err_with(|| format!("Converter command '{}' returned invalid UTF-8", cmd))
+}
@@ -0,0 +1,219 @@
+/*!
+ * Conversion of issue and merge request descriptions between markdown and the formats they can be edited in.
+ */
+use eyre::{eyre, Context, Report};
+use std::collections::HashMap;
+use std::str::FromStr;
+
+use crate::config::FormatConfig;
+
+pub mod blocks;
+pub mod command;
+pub mod org;
+pub mod pandoc;
+
+/**
+ * The backends available for converting between markdown and ORG.
+ */
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum OrgConverter {
+    /// the converter built into forge, see the `org` module
+    Builtin,
+    /// an installed pandoc executable
+    Pandoc,
+}
+
+/// names of all ORG converters, as accepted by `--converter`
+pub const ORG_CONVERTERS: &[&str] = &["builtin", "pandoc"];
+
+impl OrgConverter {
+    pub fn name(&self) -> &'static str {
+        match self {
+            OrgConverter::Builtin => "builtin",
+            OrgConverter::Pandoc => "pandoc",
+        }
+    }
+}
+
+impl FromStr for OrgConverter {
+    type Err = Report;
+
+    fn from_str(name: &str) -> Result<Self, Self::Err> {
+        match name {
+            "builtin" => Ok(OrgConverter::Builtin),
+            "pandoc" => Ok(OrgConverter::Pandoc),
+            _ => Err(eyre!(
+                "Unknown converter '{}', expected one of {}",
+                name,
+                ORG_CONVERTERS.join(", ")
+            )),
+        }
+    }
+}
+
+/// name of the format issues and merge requests are stored in by the forges
+pub const MARKDOWN: &str = "markdown";
+/// name of the ORG format, which is used by `-o`
+pub const ORG: &str = "org";
+
+/**
+ * The ways a text can be converted from and to markdown.
+ */
+#[derive(Debug, Clone, PartialEq)]
+pub enum Converter {
+    /// the text is markdown already and is not converted
+    Identity,
+    /// the ORG converter built into forge, see the `org` module
+    BuiltinOrg,
+    /// an installed pandoc executable, using the pandoc format with the given name, e.g. 'rst'
+    Pandoc(String),
+    /// shell commands reading the text from stdin and writing the converted text to stdout
+    Command {
+        from_markdown: String,
+        to_markdown: String,
+    },
+}
+
+impl Converter {
+    /// converts the given markdown text to the format of this converter
+    pub fn from_markdown(&self, text: &str) -> Result<String, Report> {
+        match self {
+            Converter::Identity => Ok(text.to_string()),
+            Converter::BuiltinOrg => Ok(org::markdown_to_org(text)),
+            Converter::Pandoc(format) => pandoc::from_markdown(text, format),
+            Converter::Command { from_markdown, .. } => {
+                command::convert_with_command(text, from_markdown)
+            }
+        }
+    }
+
+    /// converts the given text in the format of this converter to markdown
+    pub fn to_markdown(&self, text: &str) -> Result<String, Report> {
+        match self {
+            Converter::Identity => Ok(text.to_string()),
+            Converter::BuiltinOrg => Ok(org::org_to_markdown(text)),
+            Converter::Pandoc(format) => pandoc::to_markdown(text, format),
+            Converter::Command { to_markdown, .. } => {
+                command::convert_with_command(text, to_markdown)
+            }
+        }
+    }
+}
+
+/**
+ * A format issues and merge requests can be edited in. Descriptions are always uploaded as markdown.
+ */
+#[derive(Debug, Clone, PartialEq)]
+pub struct BufferFormat {
+    /// the name of the format, e.g. 'org'
+    pub name: String,
+    /// the file extension of buffers in this format, e.g. 'org'
+    pub extension: String,
+    pub converter: Converter,
+}
+
+impl BufferFormat {
+    fn new(name: &str, extension: &str, converter: Converter) -> BufferFormat {
+        BufferFormat {
+            name: name.to_string(),
+            extension: extension.to_string(),
+            converter,
+        }
+    }
+
+    /// returns the markdown format, which requires no conversion
+    pub fn markdown() -> BufferFormat {
+        BufferFormat::new(MARKDOWN, "md", Converter::Identity)
+    }
+
+    /// returns whether this is the ORG format, which has its own front matter syntax
+    pub fn is_org(&self) -> bool {
+        self.name == ORG
+    }
+
+    /// converts the given markdown text to this format
+    pub fn from_markdown(&self, text: &str) -> Result<String, Report> {
+        self.converter
+            .from_markdown(text)
+            .wrap_err_with(|| format!("Could not convert the text to {}", self.name))
+    }
+
+    /// converts the given text in this format to markdown
+    pub fn to_markdown(&self, text: &str) -> Result<String, Report> {
+        self.converter
+            .to_markdown(text)
+            .wrap_err("Could not convert the edited text back to markdown")
+    }
+}
+
+/**
+ * All formats available for editing, i.e. the built-in formats and the ones configured in the config files.
+ */
+#[derive(Debug, Clone)]
+pub struct Formats {
+    formats: Vec<BufferFormat>,
+}
+
+impl Formats {
+    /**
+     * Returns the built-in formats, converting ORG using the given converter, extended by the given
+     * configured formats. Configured formats replace built-in formats of the same name.
+     */
+    pub fn new(org_converter: OrgConverter, configured: &HashMap<String, FormatConfig>) -> Formats {
+        let org = match org_converter {
+            OrgConverter::Builtin => Converter::BuiltinOrg,
+            OrgConverter::Pandoc => Converter::Pandoc(String::from(ORG)),
+        };
+        let mut formats = vec![
+            BufferFormat::markdown(),
+            BufferFormat::new(ORG, "org", org),
+            BufferFormat::new(
+                "asciidoc",
+                "adoc",
+                Converter::Pandoc(String::from("asciidoc")),
+            ),
+            BufferFormat::new("rst", "rst", Converter::Pandoc(String::from("rst"))),
+            BufferFormat::new("djot", "dj", Converter::Pandoc(String::from("djot"))),
+        ];
+
+        let mut names: Vec<&String> = configured.keys().collect();
+        names.sort();
+        for name in names {
+            let config = &configured[name];
+            formats.retain(|format| &format.name != name);
+            formats.push(BufferFormat::new(
+                name,
+                config.extension.as_deref().unwrap_or(name),
+                Converter::Command {
+                    from_markdown: config.from_markdown.clone(),
+                    to_markdown: config.to_markdown.clone(),
+                },
+            ));
+        }
+
+        Formats { formats }
+    }
+
+    /// returns the names of all available formats
+    pub fn names(&self) -> Vec<&str> {
+        self.formats
+            .iter()
+            .map(|format| format.name.as_str())
+            .collect()
+    }
+
+    /// returns the format with the given name
+    pub fn get(&self, name: &str) -> Result<BufferFormat, Report> {
+        self.formats
+            .iter()
+            .find(|format| format.name == name)
+            .cloned()
+            .ok_or_else(|| {
+                eyre!(
+                    "Unknown format '{}', expected one of {}",
+                    name,
+                    self.names().join(", ")
+                )
+            })
+    }
+}
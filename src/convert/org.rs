@@ -0,0 +1,732 @@
+/*!
+ * Built-in converter between Gitlab flavoured markdown and ORG.
+ *
+ * The conversion works line by line and keeps the line structure intact, so unchanged paragraphs are not
+ * re-wrapped. Supported are headings, (ordered) lists, checkboxes, fenced code blocks, block quotes, tables,
+ * links, images, HTML comments and inline emphasis. Gitlab references such as `#123`, `!45` or `@user` and
+ * plain URLs are passed through unchanged.
+ */
+
+/// characters allowed in front of an opening emphasis marker
+const EMPHASIS_PRE: &str = " \t-({'\"";
+/// characters allowed after a closing emphasis marker
+const EMPHASIS_POST: &str = " \t-.,;:!?')}\"\\";
+/// file extensions of links rendered as images
+const IMAGE_EXTENSIONS: &[&str] = &[".png", ".jpg", ".jpeg", ".gif", ".svg", ".webp"];
+
+/**
+ * Converts the given Gitlab flavoured markdown text to ORG.
+ */
+pub fn markdown_to_org(text: &str) -> String {
+    let lines: Vec<&str> = text.lines().collect();
+    let mut out: Vec<String> = vec![];
+    // the fence (e.g. '```') of the currently open code block:
+    let mut fence: Option<String> = None;
+    let mut in_comment = false;
+    let mut in_quote = false;
+
+    let mut i = 0;
+    while i < lines.len() {
+        let line = lines[i];
+        let trimmed = line.trim_start();
+        let indent = &line[..line.len() - trimmed.len()];
+        i += 1;
+
+        if let Some(open_fence) = &fence {
+            if trimmed.starts_with(open_fence.as_str())
+                && trimmed
+                    .trim_start_matches(open_fence.chars().next().unwrap_or('`'))
+                    .trim()
+                    .is_empty()
+            {
+                out.push(format!("{}#+END_SRC", indent));
+                fence = None;
+            } else {
+                out.push(escape_org_code_line(line));
+            }
+            continue;
+        }
+        if in_comment {
+            if let Some(content) = trimmed.strip_suffix("-->") {
+                if !content.trim().is_empty() {
+                    out.push(content.trim_end().to_string());
+                }
+                out.push(String::from("#+END_COMMENT"));
+                in_comment = false;
+            } else {
+                out.push(line.to_string());
+            }
+            continue;
+        }
+
+        // block quotes are collected into a single quote block:
+        let quote_content =
+            trimmed
+                .strip_prefix("> ")
+                .or_else(|| if trimmed == ">" { Some("") } else { None });
+        match (quote_content, in_quote) {
+            (Some(content), _) => {
+                if !in_quote {
+                    out.push(String::from("#+BEGIN_QUOTE"));
+                    in_quote = true;
+                }
+                out.push(md_inline_to_org(content));
+                continue;
+            }
+            (None, true) => {
+                out.push(String::from("#+END_QUOTE"));
+                in_quote = false;
+            }
+            _ => {}
+        }
+
+        if let Some(marker) = fence_marker(trimmed) {
+            let lang = trimmed[marker.len()..].trim();
+            out.push(
+                format!("{}#+BEGIN_SRC {}", indent, lang)
+                    .trim_end()
+                    .to_string(),
+            );
+            fence = Some(marker);
+        } else if let Some((level, title)) = md_heading(line) {
+            out.push(format!("{} {}", "*".repeat(level), md_inline_to_org(title)));
+        } else if let Some(level) = lines.get(i).and_then(|next| setext_level(line, next)) {
+            // setext heading, e.g. 'Title' followed by '====='
+            out.push(format!(
+                "{} {}",
+                "*".repeat(level),
+                md_inline_to_org(line.trim())
+            ));
+            i += 1;
+        } else if let Some(comment) = trimmed
+            .strip_prefix("<!--")
+            .and_then(|rest| rest.trim_end().strip_suffix("-->"))
+        {
+            out.push(format!("# {}", comment.trim()).trim_end().to_string());
+        } else if let Some(rest) = trimmed.strip_prefix("<!--") {
+            out.push(String::from("#+BEGIN_COMMENT"));
+            if !rest.trim().is_empty() {
+                out.push(rest.trim().to_string());
+            }
+            in_comment = true;
+        } else if is_md_table_separator(trimmed) {
+            out.push(format!("{}{}", indent, org_table_separator(trimmed)));
+        } else if trimmed.starts_with('|') {
+            out.push(format!(
+                "{}{}",
+                indent,
+                convert_table_row(trimmed, md_inline_to_org)
+            ));
+        } else if is_md_rule(trimmed) {
+            out.push(String::from("-----"));
+        } else if let Some(item) = ListItem::parse(line) {
+            out.push(item.render(&md_inline_to_org(item.content), "[X]"));
+        } else {
+            out.push(md_inline_to_org(line));
+        }
+    }
+
+    if in_quote {
+        out.push(String::from("#+END_QUOTE"));
+    }
+    if fence.is_some() {
+        out.push(String::from("#+END_SRC"));
+    }
+
+    join_lines(&out, text)
+}
+
+/**
+ * Converts the given ORG text to Gitlab flavoured markdown.
+ */
+pub fn org_to_markdown(text: &str) -> String {
+    let mut out: Vec<String> = vec![];
+    // the kind of the currently open block, e.g. 'SRC':
+    let mut block: Option<String> = None;
+
+    for line in text.lines() {
+        let trimmed = line.trim_start();
+        let indent = &line[..line.len() - trimmed.len()];
+        let upper = trimmed.to_uppercase();
+
+        if let Some(kind) = &block {
+            if upper.starts_with(&format!("#+END_{}", kind)) {
+                // block quotes end without a closing line in markdown:
+                match kind.as_str() {
+                    "SRC" | "EXAMPLE" => out.push(format!("{}```", indent)),
+                    "COMMENT" => out.push(String::from("-->")),
+                    _ => {}
+                }
+                block = None;
+                continue;
+            }
+            out.push(match kind.as_str() {
+                "SRC" | "EXAMPLE" => unescape_org_code_line(line),
+                "QUOTE" if trimmed.is_empty() => String::from(">"),
+                "QUOTE" => format!("> {}", org_inline_to_md(trimmed)),
+                _ => line.to_string(),
+            });
+            continue;
+        }
+
+        if let Some(rest) = upper.strip_prefix("#+BEGIN_") {
+            let kind = rest
+                .split_whitespace()
+                .next()
+                .unwrap_or_default()
+                .to_string();
+            let args = trimmed
+                .get("#+BEGIN_".len() + kind.len()..)
+                .unwrap_or_default()
+                .trim();
+            match kind.as_str() {
+                "SRC" => {
+                    let lang = args.split_whitespace().next().unwrap_or_default();
+                    out.push(format!("{}```{}", indent, lang));
+                }
+                "EXAMPLE" => out.push(format!("{}```", indent)),
+                "COMMENT" => out.push(String::from("<!--")),
+                "QUOTE" => {}
+                _ => {
+                    // unknown blocks are kept as they are
+                    out.push(line.to_string());
+                    continue;
+                }
+            }
+            block = Some(kind);
+        } else if let Some((level, title)) = org_heading(line) {
+            out.push(format!("{} {}", "#".repeat(level), org_inline_to_md(title)));
+        } else if trimmed == "#" || trimmed.starts_with("# ") {
+            out.push(format!("<!-- {} -->", trimmed[1..].trim()));
+        } else if trimmed.starts_with("#+") {
+            // keywords such as '#+TITLE:' have no markdown equivalent
+            out.push(line.to_string());
+        } else if is_org_table_separator(trimmed) {
+            let columns = trimmed.matches('+').count() + 1;
+            out.push(format!("{}|{}", indent, " --- |".repeat(columns)));
+        } else if trimmed.starts_with('|') {
+            out.push(format!(
+                "{}{}",
+                indent,
+                convert_table_row(trimmed, org_inline_to_md)
+            ));
+        } else if trimmed.len() >= 5 && trimmed.chars().all(|c| c == '-') {
+            out.push(String::from("---"));
+        } else if let Some(item) = ListItem::parse(line).filter(|item| item.marker != "*") {
+            out.push(item.render(&org_inline_to_md(item.content), "[x]"));
+        } else {
+            out.push(org_inline_to_md(line));
+        }
+    }
+
+    join_lines(&out, text)
+}
+
+/// joins the given lines, keeping the trailing newline of the original text
+fn join_lines(lines: &[String], original: &str) -> String {
+    let mut joined = lines.join("\n");
+    if original.ends_with('\n') {
+        joined.push('\n');
+    }
+    joined
+}
+
+/// returns the fence of a fenced code block starting at the given line, e.g. '```'
+pub(crate) fn fence_marker(trimmed: &str) -> Option<String> {
+    ['`', '~'].iter().find_map(|c| {
+        let marker: String = trimmed.chars().take_while(|ch| ch == c).collect();
+        if marker.len() >= 3 {
+            Some(marker)
+        } else {
+            None
+        }
+    })
+}
+
+/// lines in ORG source blocks starting with '*' or '#+' must be escaped with a comma
+fn escape_org_code_line(line: &str) -> String {
+    let trimmed = line.trim_start();
+    if trimmed.starts_with('*') || trimmed.starts_with("#+") || trimmed.starts_with(",*") {
+        let indent = &line[..line.len() - trimmed.len()];
+        format!("{},{}", indent, trimmed)
+    } else {
+        line.to_string()
+    }
+}
+
+fn unescape_org_code_line(line: &str) -> String {
+    let trimmed = line.trim_start();
+    match trimmed.strip_prefix(',') {
+        Some(rest) if rest.starts_with('*') || rest.starts_with("#+") || rest.starts_with(",*") => {
+            let indent = &line[..line.len() - trimmed.len()];
+            format!("{}{}", indent, rest)
+        }
+        _ => line.to_string(),
+    }
+}
+
+/// parses an ATX heading, e.g. '## Title'
+fn md_heading(line: &str) -> Option<(usize, &str)> {
+    let level = line.chars().take_while(|c| *c == '#').count();
+    let rest = &line[level..];
+    if (1..=6).contains(&level) && (rest.is_empty() || rest.starts_with(' ')) {
+        let title = rest.trim().trim_end_matches('#').trim_end();
+        Some((level, title))
+    } else {
+        None
+    }
+}
+
+/// returns the level of a setext heading made of the given line and its underline
+fn setext_level(line: &str, next: &str) -> Option<usize> {
+    let underline = next.trim();
+    if line.trim().is_empty() || line.starts_with(' ') || underline.is_empty() {
+        return None;
+    }
+    if ListItem::parse(line).is_some() || line.trim_start().starts_with('|') {
+        return None;
+    }
+    if underline.chars().all(|c| c == '=') {
+        Some(1)
+    } else if underline.chars().all(|c| c == '-') {
+        Some(2)
+    } else {
+        None
+    }
+}
+
+/// parses an ORG heading, e.g. '** Title'
+fn org_heading(line: &str) -> Option<(usize, &str)> {
+    let level = line.chars().take_while(|c| *c == '*').count();
+    let rest = &line[level..];
+    if level > 0 && rest.starts_with(' ') {
+        Some((level, rest.trim()))
+    } else {
+        None
+    }
+}
+
+/// returns whether the given line is a horizontal rule, e.g. '---' or '* * *'
+fn is_md_rule(trimmed: &str) -> bool {
+    let chars: Vec<char> = trimmed.chars().filter(|c| !c.is_whitespace()).collect();
+    chars.len() >= 3 && ['-', '*', '_'].iter().any(|m| chars.iter().all(|c| c == m))
+}
+
+/// returns whether the given line separates the header of a markdown table, e.g. '| --- | :-: |'
+fn is_md_table_separator(trimmed: &str) -> bool {
+    trimmed.contains('|')
+        && trimmed.contains('-')
+        && trimmed
+            .chars()
+            .all(|c| matches!(c, '|' | '-' | ':' | ' ' | '\t'))
+}
+
+/// returns whether the given line is a horizontal line of an ORG table, e.g. '|---+---|'
+fn is_org_table_separator(trimmed: &str) -> bool {
+    trimmed.starts_with("|-") && trimmed.chars().all(|c| matches!(c, '|' | '-' | '+'))
+}
+
+/// returns the ORG table line for the given markdown table separator
+fn org_table_separator(trimmed: &str) -> String {
+    let cells: Vec<String> = split_table_cells(trimmed)
+        .iter()
+        .map(|cell| "-".repeat(cell.trim().len().max(3) + 2))
+        .collect();
+    format!("|{}|", cells.join("+"))
+}
+
+/// splits a table row into its cells, ignoring the outer pipes
+fn split_table_cells(row: &str) -> Vec<&str> {
+    let row = row.trim();
+    let row = row.strip_prefix('|').unwrap_or(row);
+    let row = row.strip_suffix('|').unwrap_or(row);
+    row.split('|').collect()
+}
+
+/// converts the cells of a table row using the given inline conversion
+fn convert_table_row(trimmed: &str, convert: fn(&str) -> String) -> String {
+    let cells: Vec<String> = split_table_cells(trimmed)
+        .iter()
+        .map(|cell| format!(" {} ", convert(cell.trim())))
+        .collect();
+    format!("|{}|", cells.join("|"))
+}
+
+/**
+ * An item of an ordered or unordered list, possibly with a checkbox.
+ * The syntax of list items is almost identical in markdown and ORG.
+ */
+struct ListItem<'a> {
+    indent: &'a str,
+    /// e.g. '-', '*' or '1.'
+    marker: &'a str,
+    checked: Option<bool>,
+    content: &'a str,
+}
+
+impl<'a> ListItem<'a> {
+    fn parse(line: &'a str) -> Option<ListItem<'a>> {
+        let trimmed = line.trim_start();
+        let indent = &line[..line.len() - trimmed.len()];
+
+        let digits = trimmed.chars().take_while(|c| c.is_ascii_digit()).count();
+        let marker_len = if ["- ", "* ", "+ "].iter().any(|m| trimmed.starts_with(m)) {
+            1
+        } else if digits > 0 && matches!(trimmed[digits..].chars().next(), Some('.') | Some(')')) {
+            digits + 1
+        } else {
+            return None;
+        };
+        let marker = &trimmed[..marker_len];
+        let rest = &trimmed[marker_len..];
+        if !(rest.starts_with(' ') || rest.is_empty()) {
+            return None;
+        }
+        let rest = rest.trim_start();
+
+        let (checked, content) = match rest.get(..4) {
+            Some("[ ] ") => (Some(false), &rest[4..]),
+            Some("[x] ") | Some("[X] ") => (Some(true), &rest[4..]),
+            Some("[-] ") => (Some(false), &rest[4..]),
+            _ => match rest {
+                "[ ]" | "[-]" => (Some(false), ""),
+                "[x]" | "[X]" => (Some(true), ""),
+                _ => (None, rest),
+            },
+        };
+
+        Some(ListItem {
+            indent,
+            marker,
+            checked,
+            content,
+        })
+    }
+
+    /// renders this item with the given converted content, using the given marker for checked checkboxes
+    fn render(&self, content: &str, checked_box: &str) -> String {
+        // '*' bullets would be headings in ORG and are less common in markdown:
+        let marker = if self.marker == "*" || self.marker == "+" {
+            "-"
+        } else {
+            self.marker
+        };
+        let checkbox = match self.checked {
+            Some(true) => format!("{} ", checked_box),
+            Some(false) => String::from("[ ] "),
+            None => String::new(),
+        };
+        format!("{}{} {}{}", self.indent, marker, checkbox, content)
+            .trim_end()
+            .to_string()
+    }
+}
+
+/**
+ * Returns the index of the closing emphasis marker for an opening marker at `start`,
+ * following the ORG rules: the opening marker must be followed by a non-whitespace character,
+ * the closing marker must be preceded by one and be followed by whitespace or punctuation.
+ */
+fn find_emphasis(chars: &[char], start: usize, marker: &str) -> Option<usize> {
+    let marker: Vec<char> = marker.chars().collect();
+    let len = marker.len();
+    if chars.get(start..start + len) != Some(&marker[..]) {
+        return None;
+    }
+    if start > 0 && !EMPHASIS_PRE.contains(chars[start - 1]) {
+        return None;
+    }
+    match chars.get(start + len) {
+        Some(c) if !c.is_whitespace() && *c != marker[0] => {}
+        _ => return None,
+    }
+
+    let mut end = start + len + 1;
+    while end + len <= chars.len() {
+        if chars[end..end + len] == marker[..]
+            && !chars[end - 1].is_whitespace()
+            && chars
+                .get(end + len)
+                .is_none_or(|c| EMPHASIS_POST.contains(*c) || c.is_whitespace())
+        {
+            return Some(end);
+        }
+        end += 1;
+    }
+    None
+}
+
+/// returns the length of a plain URL starting at the given index, if any
+fn url_length(chars: &[char], start: usize) -> Option<usize> {
+    let rest: String = chars[start..].iter().take(8).collect();
+    if start > 0 && chars[start - 1].is_alphanumeric() {
+        return None;
+    }
+    if rest.starts_with("http://") || rest.starts_with("https://") {
+        let len = chars[start..]
+            .iter()
+            .take_while(|c| !c.is_whitespace() && !matches!(c, '<' | '>' | ')' | ']'))
+            .count();
+        Some(len)
+    } else {
+        None
+    }
+}
+
+/// returns the index of the given closing character after `start`, respecting nesting
+fn find_closing(chars: &[char], start: usize, open: char, close: char) -> Option<usize> {
+    let mut depth = 0;
+    for (i, c) in chars.iter().enumerate().skip(start) {
+        if *c == open {
+            depth += 1;
+        } else if *c == close {
+            depth -= 1;
+            if depth == 0 {
+                return Some(i);
+            }
+        }
+    }
+    None
+}
+
+fn is_image(url: &str) -> bool {
+    let url = url.to_lowercase();
+    IMAGE_EXTENSIONS.iter().any(|ext| url.ends_with(ext))
+}
+
+/**
+ * Converts inline markdown (emphasis, code spans, links) to ORG.
+ */
+fn md_inline_to_org(text: &str) -> String {
+    let chars: Vec<char> = text.chars().collect();
+    let mut out = String::new();
+    let mut i = 0;
+
+    while i < chars.len() {
+        let c = chars[i];
+
+        // code spans:
+        if c == '`' {
+            let ticks = chars[i..].iter().take_while(|c| **c == '`').count();
+            let fence: String = "`".repeat(ticks);
+            let rest: String = chars[i + ticks..].iter().collect();
+            if let Some(end) = rest.find(&fence) {
+                let code = rest[..end].trim();
+                let marker = if code.contains('~') { '=' } else { '~' };
+                out.push_str(&format!("{}{}{}", marker, code, marker));
+                i += ticks + rest[..end].chars().count() + ticks;
+                continue;
+            }
+        }
+
+        // escaped characters are kept as they are:
+        if c == '\\' && i + 1 < chars.len() {
+            out.push(c);
+            out.push(chars[i + 1]);
+            i += 2;
+            continue;
+        }
+
+        // links and images:
+        let is_image_link = c == '!' && chars.get(i + 1) == Some(&'[');
+        if c == '[' || is_image_link {
+            let open = if is_image_link { i + 1 } else { i };
+            if let Some(close) = find_closing(&chars, open, '[', ']') {
+                if chars.get(close + 1) == Some(&'(') {
+                    if let Some(end) = find_closing(&chars, close + 1, '(', ')') {
+                        let label: String = chars[open + 1..close].iter().collect();
+                        let url: String = chars[close + 2..end].iter().collect();
+                        let url = url.split_whitespace().next().unwrap_or_default();
+                        // the alt text of images is kept as description of the ORG link:
+                        if label.is_empty() {
+                            out.push_str(&format!("[[{}]]", url));
+                        } else if is_image_link {
+                            out.push_str(&format!("[[{}][{}]]", url, label));
+                        } else {
+                            out.push_str(&format!("[[{}][{}]]", url, md_inline_to_org(&label)));
+                        }
+                        i = end + 1;
+                        continue;
+                    }
+                }
+            }
+        }
+
+        // autolinks, e.g. '<https://example.com>':
+        if c == '<' {
+            if let Some(len) = url_length(&chars, i + 1) {
+                if chars.get(i + 1 + len) == Some(&'>') {
+                    let url: String = chars[i + 1..i + 1 + len].iter().collect();
+                    out.push_str(&format!("[[{}]]", url));
+                    i += len + 2;
+                    continue;
+                }
+            }
+        }
+
+        // plain URLs must not be touched by the emphasis conversion:
+        if let Some(len) = url_length(&chars, i) {
+            out.extend(&chars[i..i + len]);
+            i += len;
+            continue;
+        }
+
+        // emphasis, the longer markers first:
+        let emphasis = [
+            ("**", "*"),
+            ("__", "*"),
+            ("~~", "+"),
+            ("*", "/"),
+            ("_", "/"),
+        ]
+        .iter()
+        .find_map(|(md, org)| {
+            find_emphasis(&chars, i, md).map(|end| (md.chars().count(), end, *org))
+        });
+        if let Some((len, end, org_marker)) = emphasis {
+            let content: String = chars[i + len..end].iter().collect();
+            out.push_str(&format!(
+                "{}{}{}",
+                org_marker,
+                md_inline_to_org(&content),
+                org_marker
+            ));
+            i = end + len;
+            continue;
+        }
+
+        out.push(c);
+        i += 1;
+    }
+
+    out
+}
+
+/**
+ * Converts inline ORG (emphasis, verbatim, links) to markdown.
+ */
+fn org_inline_to_md(text: &str) -> String {
+    let chars: Vec<char> = text.chars().collect();
+    let mut out = String::new();
+    let mut i = 0;
+
+    while i < chars.len() {
+        let c = chars[i];
+
+        // links, e.g. '[[https://example.com][example]]':
+        if c == '[' && chars.get(i + 1) == Some(&'[') {
+            if let Some(end) = chars[i + 2..].windows(2).position(|w| w == [']', ']']) {
+                let link: String = chars[i + 2..i + 2 + end].iter().collect();
+                let (url, label) = match link.split_once("][") {
+                    Some((url, label)) => (url, Some(label)),
+                    None => (link.as_str(), None),
+                };
+                let url = url.strip_prefix("file:").unwrap_or(url);
+                out.push_str(&match label {
+                    Some(label) if is_image(url) => format!("![{}]({})", label, url),
+                    Some(label) => format!("[{}]({})", org_inline_to_md(label), url),
+                    None if is_image(url) => format!("![]({})", url),
+                    None if url.contains("://") => format!("<{}>", url),
+                    None => format!("[{}]({})", url, url),
+                });
+                i += 2 + end + 2;
+                continue;
+            }
+        }
+
+        if let Some(len) = url_length(&chars, i) {
+            out.extend(&chars[i..i + len]);
+            i += len;
+            continue;
+        }
+
+        // verbatim and code are not converted any further:
+        if let Some(end) = ['~', '=']
+            .iter()
+            .find_map(|m| find_emphasis(&chars, i, &m.to_string()))
+        {
+            let code: String = chars[i + 1..end].iter().collect();
+            let fence = if code.contains('`') { "``" } else { "`" };
+            out.push_str(&format!("{}{}{}", fence, code, fence));
+            i = end + 1;
+            continue;
+        }
+
+        // underlined text has no markdown equivalent and is kept as it is:
+        let emphasis = [("*", "**"), ("/", "*"), ("+", "~~")]
+            .iter()
+            .find_map(|(org, md)| find_emphasis(&chars, i, org).map(|end| (end, *md)));
+        if let Some((end, md_marker)) = emphasis {
+            let content: String = chars[i + 1..end].iter().collect();
+            out.push_str(&format!(
+                "{}{}{}",
+                md_marker,
+                org_inline_to_md(&content),
+                md_marker
+            ));
+            i = end + 1;
+            continue;
+        }
+
+        out.push(c);
+        i += 1;
+    }
+
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_round_trip(markdown: &str) {
+        let org = markdown_to_org(markdown);
+        assert_eq!(
+            org_to_markdown(&org),
+            markdown,
+            "converted via ORG:\n{}",
+            org
+        );
+    }
+
+    #[test]
+    fn converts_links_with_multibyte_labels() {
+        assert_eq!(
+            org_inline_to_md("Prüfe [[https://x.com][Ärger]] jetzt"),
+            "Prüfe [Ärger](https://x.com) jetzt"
+        );
+        assert_eq!(
+            md_inline_to_org("Prüfe [Ärger](https://x.com) jetzt"),
+            "Prüfe [[https://x.com][Ärger]] jetzt"
+        );
+    }
+
+    #[test]
+    fn round_trips_inline_markup() {
+        assert_round_trip("[Größe](https://x.com/a) and `code`\n");
+        assert_round_trip("Übergröße **fett** und *kursiv* mit `code` und ~~weg~~\n");
+        assert_round_trip("Siehe <https://example.com/ä> oder ![Bild](bild.png)\n");
+    }
+
+    #[test]
+    fn round_trips_headings() {
+        assert_round_trip("# Überschrift\n\nText\n\n## Zweite Ebene\n\nMehr Text\n");
+    }
+
+    #[test]
+    fn round_trips_lists() {
+        assert_round_trip("- erster Punkt\n- zweiter [Link](https://x.com)\n  - verschachtelt\n");
+        assert_round_trip("1. eins\n2. zwei\n");
+        assert_round_trip("- [ ] offen\n- [x] erledigt\n");
+    }
+
+    #[test]
+    fn round_trips_tables() {
+        assert_round_trip("| Größe | Wert |\n| --- | --- |\n| `ä` | [ö](https://x.com) |\n");
+    }
+
+    #[test]
+    fn round_trips_code_blocks() {
+        assert_round_trip("```rust\nlet ä = \"ö\";\n```\n");
+    }
+}
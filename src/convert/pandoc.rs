@@ -0,0 +1,38 @@
+/*!
+ * Conversion between markdown and other formats using an installed pandoc executable.
+ */
+use cmd_lib::run_cmd;
+use eyre::{Context, Report};
+use std::fs::{self, File};
+use std::io::Write;
+use tempfile::tempdir;
+
+/**
+ * Converts the given text between two formats supported by pandoc, e.g. 'markdown' and 'org'.
+ */
+fn convert_with_pandoc(text: &str, from: &str, to: &str) -> Result<String, Report> {
+    let tmp_dir = tempdir().wrap_err("Could not create temp dir")?;
+
+    let input_path = tmp_dir.path().join(format!("input.{}", from));
+    let output_path = tmp_dir.path().join(format!("output.{}", to));
+    let input_file = File::create(&input_path)?;
+    write!(&input_file, "{}", text)?;
+
+    run_cmd!(pandoc -f $from -t $to $input_path -o $output_path)
+        .wrap_err("Failed to run pandoc. Is it installed?")?;
+    let converted_text = fs::read_to_string(&output_path)?;
+
+    tmp_dir.close()?;
+
+    Ok(converted_text)
+}
+
+/// converts the given markdown text to the given pandoc format, e.g. 'rst'
+pub fn from_markdown(text: &str, format: &str) -> Result<String, Report> {
+    convert_with_pandoc(text, "markdown", format)
+}
+
+/// converts the given text in the given pandoc format, e.g. 'rst', to markdown
+pub fn to_markdown(text: &str, format: &str) -> Result<String, Report> {
+    convert_with_pandoc(text, format, "markdown")
+}
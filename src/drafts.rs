@@ -0,0 +1,262 @@
+use color_eyre::Section;
+use eyre::{eyre, Context, Report};
+use log::warn;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::fs;
+use std::future::Future;
+use std::path::PathBuf;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::{
+    cli::GlobalArgs, convert::BufferFormat, editor::buffer_file_name,
+    front_matter::MergeRequestFields,
+};
+
+/**
+ * The commands a draft can be created by, together with the details required to upload it later.
+ */
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "command", rename_all = "kebab-case")]
+pub enum DraftKind {
+    CreateIssue {
+        labels: Vec<String>,
+    },
+    EditIssue {
+        iid: String,
+        original_title: String,
+        original_description: String,
+    },
+    CreateMergeRequest {
+        source_branch: String,
+        target_branch: String,
+    },
+    EditMergeRequest {
+        branch: String,
+        /// the iid of the edited merge request
+        iid: String,
+        /// the description as fetched before editing it, used to merge concurrent remote changes
+        original_description: String,
+        /// the attributes as fetched before editing them, used to upload changed attributes only
+        original_fields: MergeRequestFields,
+    },
+}
+
+impl fmt::Display for DraftKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DraftKind::CreateIssue { .. } => write!(f, "new issue"),
+            DraftKind::EditIssue { iid, .. } => write!(f, "issue {}", iid),
+            DraftKind::CreateMergeRequest {
+                source_branch,
+                target_branch,
+            } => write!(
+                f,
+                "new merge request {} -> {}",
+                source_branch, target_branch
+            ),
+            DraftKind::EditMergeRequest { branch, .. } => write!(f, "merge request of {}", branch),
+        }
+    }
+}
+
+/**
+ * An edited issue or merge request which has not been uploaded successfully yet.
+ * Drafts are stored as JSON files in the cache directory until the upload succeeds or they are discarded.
+ */
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Draft {
+    /// number identifying this draft, derived from its file name
+    #[serde(skip)]
+    pub id: u32,
+    pub host: String,
+    pub project_path: String,
+    /// seconds since the UNIX epoch
+    pub created_at: u64,
+    /// the name of the format of `text`, e.g. 'org'
+    pub format: String,
+    /// the content of the editor buffer
+    pub text: String,
+    #[serde(flatten)]
+    pub kind: DraftKind,
+}
+
+/// returns the directory drafts are stored in, e.g. '~/.cache/forge/drafts'
+fn drafts_dir() -> Result<PathBuf, Report> {
+    dirs::cache_dir()
+        .map(|dir| dir.join("forge").join("drafts"))
+        .ok_or_else(|| eyre!("Could not locate the cache directory"))
+}
+
+fn draft_path(id: u32) -> Result<PathBuf, Report> {
+    Ok(drafts_dir()?.join(format!("{}.json", id)))
+}
+
+impl Draft {
+    /**
+     * Saves the given editor buffer as new draft for the project of the given global arguments.
+     */
+    pub fn create(
+        kind: DraftKind,
+        text: &str,
+        format: &BufferFormat,
+        global_args: &GlobalArgs,
+    ) -> Result<Draft, Report> {
+        let created_at = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|duration| duration.as_secs())
+            .unwrap_or_default();
+
+        // use the lowest free id, so ids stay short. Only the file names are read,
+        // so an invalid draft does not prevent saving the edited text:
+        let existing_ids = draft_ids()?;
+        let id = (1..)
+            .find(|id| !existing_ids.contains(id))
+            .unwrap_or_default();
+
+        let draft = Draft {
+            id,
+            host: global_args.host.clone(),
+            project_path: global_args.project_path.clone(),
+            created_at,
+            format: format.name.clone(),
+            text: text.to_string(),
+            kind,
+        };
+        draft.save()?;
+
+        Ok(draft)
+    }
+
+    /// loads the draft with the given id
+    pub fn load(id: u32) -> Result<Draft, Report> {
+        let path = draft_path(id)?;
+        let content = fs::read_to_string(&path)
+            .wrap_err_with(|| format!("Draft {} not found", id))
+            .suggestion("Run 'forge drafts list' to show all available drafts")?;
+        let mut draft: Draft =
+            serde_json::from_str(&content).wrap_err_with(|| format!("Invalid draft {:?}", path))?;
+        draft.id = id;
+
+        Ok(draft)
+    }
+
+    /// writes this draft to the drafts directory, overwriting a previous version
+    pub fn save(&self) -> Result<(), Report> {
+        let dir = drafts_dir()?;
+        fs::create_dir_all(&dir).wrap_err_with(|| format!("Could not create {:?}", dir))?;
+
+        let path = draft_path(self.id)?;
+        fs::write(&path, serde_json::to_string_pretty(self)?)
+            .wrap_err_with(|| format!("Could not save the draft to {:?}", path))
+    }
+
+    /// deletes this draft
+    pub fn discard(&self) -> Result<(), Report> {
+        let path = draft_path(self.id)?;
+        fs::remove_file(&path).wrap_err_with(|| format!("Could not delete the draft {:?}", path))
+    }
+
+    /// returns the name of the file used to edit this draft in the given format, e.g. 'draft_1.md'
+    pub fn file_name(&self, format: &BufferFormat) -> String {
+        buffer_file_name(&format!("draft_{}", self.id), format)
+    }
+
+    /// returns a one-line summary of this draft for listing it
+    pub fn summary(&self) -> String {
+        let first_line = self
+            .text
+            .lines()
+            .find(|line| !line.trim().is_empty() && line.trim() != "+++")
+            .unwrap_or_default();
+
+        format!(
+            "{:>3}  {:<12}  {:<30}  {}  {}",
+            self.id,
+            format_age(self.created_at),
+            format!("{} ({})", self.project_path, self.kind),
+            self.host,
+            first_line.trim()
+        )
+    }
+
+    /// returns this draft as JSON object including its id, which is not part of the stored file
+    pub fn to_json(&self) -> Result<serde_json::Value, Report> {
+        let mut json = serde_json::to_value(self)?;
+        json["id"] = self.id.into();
+        Ok(json)
+    }
+
+    /**
+     * Runs the given upload of this draft. The draft is discarded if the upload succeeds
+     * and kept for a later retry otherwise.
+     */
+    pub async fn upload<F>(&self, upload: F) -> Result<(), Report>
+    where
+        F: Future<Output = Result<(), Report>>,
+    {
+        match upload.await {
+            Ok(()) => self.discard(),
+            Err(err) => Err(err).suggestion(format!(
+                "Your text was saved as draft {}. Run 'forge drafts resume {}' to retry the upload",
+                self.id, self.id
+            )),
+        }
+    }
+}
+
+/// returns how long ago the given UNIX timestamp was, e.g. '5 min ago'
+fn format_age(timestamp: u64) -> String {
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_secs())
+        .unwrap_or_default();
+
+    match now.saturating_sub(timestamp) {
+        secs if secs < 60 => String::from("just now"),
+        secs if secs < 60 * 60 => format!("{} min ago", secs / 60),
+        secs if secs < 60 * 60 * 24 => format!("{} h ago", secs / 60 / 60),
+        secs => format!("{} days ago", secs / 60 / 60 / 24),
+    }
+}
+
+/// returns the ids of all files in the drafts directory, without reading them
+fn draft_ids() -> Result<Vec<u32>, Report> {
+    let dir = drafts_dir()?;
+    if !dir.exists() {
+        return Ok(vec![]);
+    }
+
+    let mut ids = vec![];
+    for entry in fs::read_dir(&dir).wrap_err_with(|| format!("Could not read {:?}", dir))? {
+        let path = match entry {
+            Ok(entry) => entry.path(),
+            Err(_) => continue,
+        };
+        let id = path
+            .file_stem()
+            .and_then(|stem| stem.to_str())
+            .and_then(|stem| stem.parse().ok());
+        if let (Some(id), Some("json")) = (id, path.extension().and_then(|ext| ext.to_str())) {
+            ids.push(id);
+        }
+    }
+    ids.sort_unstable();
+
+    Ok(ids)
+}
+
+/**
+ * Returns all stored drafts, ordered by their id. Drafts which cannot be loaded are skipped with a warning.
+ */
+pub fn list_drafts() -> Result<Vec<Draft>, Report> {
+    let mut drafts = vec![];
+    for id in draft_ids()? {
+        match Draft::load(id) {
+            Ok(draft) => drafts.push(draft),
+            Err(err) => warn!("Skipping draft {}: {}: {}", id, err, err.root_cause()),
+        }
+    }
+
+    Ok(drafts)
+}
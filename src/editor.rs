@@ -0,0 +1,350 @@
+use eyre::{eyre, Context, Report};
+use std::fs::{self, File};
+use std::io::{Read, Write};
+use std::path::Path;
+use std::process::Command;
+use tempfile::tempdir;
+
+use crate::{
+    convert::{blocks, BufferFormat, Converter},
+    errors::AppError,
+};
+
+/// returns the name of the file used to edit a buffer, e.g. 'new_issue.org'
+pub fn buffer_file_name(file_stem: &str, format: &BufferFormat) -> String {
+    format!("{}.{}", file_stem, format.extension)
+}
+
+/**
+ * Returns the editor buffer for the given markdown text, which is converted to the given format.
+ * With the built-in ORG converter, the text is converted block by block, so unchanged blocks can be restored
+ * using `from_edited_buffer`.
+ */
+pub fn to_buffer(text: &str, format: &BufferFormat) -> Result<String, Report> {
+    if format.converter == Converter::BuiltinOrg {
+        Ok(blocks::to_org(text))
+    } else {
+        format.from_markdown(text)
+    }
+}
+
+/// returns the markdown text of the given editor buffer, which is converted from the given format
+pub fn from_buffer(buffer: &str, format: &BufferFormat) -> Result<String, Report> {
+    format.to_markdown(buffer)
+}
+
+/**
+ * Returns the markdown text of the given editor buffer, which has been created from the `original`
+ * markdown text using `to_buffer`. With the built-in ORG converter, only the blocks which have been changed
+ * are converted back, all other blocks are restored from the original text verbatim.
+ */
+pub fn from_edited_buffer(
+    original: &str,
+    buffer: &str,
+    format: &BufferFormat,
+) -> Result<String, Report> {
+    if format.converter == Converter::BuiltinOrg {
+        Ok(blocks::restore_unchanged(original, buffer))
+    } else {
+        format.to_markdown(buffer)
+    }
+}
+
+/// line separating the editable text from the comments appended to a buffer, similar to `git commit -v`
+const SCISSORS_LINE: &str = "# ------------------------ >8 ------------------------";
+
+/**
+ * Appends the given comment lines to the given buffer, separated by a scissors line.
+ * Markdown headings and converted HTML comments also start with '#', so only lines below the scissors line
+ * are treated as comments.
+ */
+pub fn append_comments(buffer: &str, comments: &[String]) -> String {
+    let mut lines = vec![
+        String::new(),
+        SCISSORS_LINE.to_string(),
+        String::from("# Do not modify or remove the line above."),
+        String::from("# Everything below it will be ignored."),
+    ];
+    lines.extend(comments.iter().map(|comment| format!("# {}", comment)));
+
+    format!("{}\n{}\n", buffer.trim_end(), lines.join("\n"))
+}
+
+/**
+ * Removes the last scissors line and all comments below it from the given buffer,
+ * i.e. the block appended by `append_comments`. Buffers without a scissors line are returned unchanged,
+ * apart from trailing whitespace.
+ */
+pub fn strip_comments(buffer: &str) -> String {
+    let text = match buffer
+        .match_indices(SCISSORS_LINE)
+        .map(|(index, _)| index)
+        .filter(|index| *index == 0 || buffer[..*index].ends_with('\n'))
+        .last()
+    {
+        Some(index) => &buffer[..index],
+        None => buffer,
+    };
+    let text = text.trim_end();
+
+    if text.is_empty() {
+        String::new()
+    } else {
+        format!("{}\n", text)
+    }
+}
+
+/**
+ * Opens the given buffer with the given comments appended in the editor and returns the edited buffer
+ * without the comments. `file_name` is used as name of the temporary file, e.g. 'new_issue.md'.
+ * The cursor is placed on the given line, if supported by the editor command.
+ */
+pub fn edit_buffer(
+    file_name: &str,
+    buffer: &str,
+    line: usize,
+    comments: &[String],
+    editor_command: &str,
+) -> Result<String, Report> {
+    let edited = edit_file(
+        file_name,
+        &append_comments(buffer, comments),
+        line,
+        editor_command,
+    )?;
+    Ok(strip_comments(&edited))
+}
+
+/// fails with `AppError::Aborted` if the edited buffer only differs from the original one in trailing whitespace
+pub fn check_changed(original: &str, edited: &str) -> Result<(), Report> {
+    if original.trim_end() == edited.trim_end() {
+        return Err(AppError::Aborted(String::from("no changes were made")).into());
+    }
+    Ok(())
+}
+
+/// fails with `AppError::Aborted` if the given buffer is empty
+pub fn check_not_empty(buffer: &str) -> Result<(), Report> {
+    if buffer.trim().is_empty() {
+        return Err(AppError::Aborted(String::from("the text is empty")).into());
+    }
+    Ok(())
+}
+
+/**
+ * Fails with `AppError::Aborted` if the title of the given editor buffer is empty. The buffer is converted
+ * to markdown and split by `split_required_title` exactly like it is before uploading it.
+ */
+pub fn check_title(buffer: &str, format: &BufferFormat) -> Result<(), Report> {
+    split_required_title(&from_buffer(buffer, format)?).map(|_| ())
+}
+
+/// placeholder in the editor command which is replaced by the path of the edited file
+const FILE_PLACEHOLDER: &str = "{file}";
+/// placeholder in the editor command which is replaced by the line to place the cursor on
+const LINE_PLACEHOLDER: &str = "{line}";
+
+/**
+ * Returns the program and arguments to run for editing the given file with the given editor command,
+ * e.g. 'code --wait' or "emacsclient -c -a ''". The command is split into words like in a shell.
+ * The placeholders '{file}' and '{line}' are replaced, the file is appended if there is no '{file}' placeholder.
+ */
+fn editor_args(editor_command: &str, file_path: &Path, line: usize) -> Result<Vec<String>, Report> {
+    let words = shell_words::split(editor_command)
+        .wrap_err_with(|| format!("Invalid editor command '{}'", editor_command))?;
+    let file = file_path.to_string_lossy();
+    let has_file_placeholder = words.iter().any(|word| word.contains(FILE_PLACEHOLDER));
+
+    let mut args: Vec<String> = words
+        .iter()
+        .map(|word| {
+            word.replace(FILE_PLACEHOLDER, &file)
+                .replace(LINE_PLACEHOLDER, &line.to_string())
+        })
+        .collect();
+    if !has_file_placeholder {
+        args.push(file.to_string());
+    }
+    if args.len() < 2 {
+        return Err(eyre!("The editor command is empty"));
+    }
+
+    Ok(args)
+}
+
+/**
+ * Writes the given content to a temporary file with the given name, e.g. 'new_issue.md',
+ * opens it in the editor with the cursor on the given line and returns the edited content.
+ */
+pub fn edit_file(
+    file_name: &str,
+    content: &str,
+    line: usize,
+    editor_command: &str,
+) -> Result<String, Report> {
+    let tmp_dir = tempdir().wrap_err("Could not create temp dir")?;
+
+    let file_path = tmp_dir.path().join(file_name);
+    let file = File::create(&file_path)?;
+    write!(&file, "{}", content)?;
+
+    let args = editor_args(editor_command, &file_path, line)?;
+    let status = Command::new(&args[0])
+        .args(&args[1..])
+        .status()
+        .wrap_err_with(|| {
+            format!(
+                "Could not open {:?} in editor {}",
+                file_path, editor_command
+            )
+        })?;
+    if !status.success() {
+        return Err(eyre!("Editor {} exited with {}", editor_command, status));
+    }
+
+    let edited_content = fs::read_to_string(&file_path)
+        .wrap_err_with(|| format!("Failed to read the edited text from {:?}", file_path))?;
+
+    // cleanup temp files
+    tmp_dir.close()?;
+
+    Ok(edited_content)
+}
+
+/**
+ * Reads the text of a buffer from the file at the given path, or from stdin if the path is '-'.
+ * Used instead of `edit_buffer` for non-interactive input. Leading empty lines and comments below
+ * a scissors line are removed.
+ */
+pub fn read_input(path: &str) -> Result<String, Report> {
+    let text = if path == "-" {
+        let mut text = String::new();
+        std::io::stdin()
+            .read_to_string(&mut text)
+            .wrap_err("Failed to read the text from stdin")?;
+        text
+    } else {
+        fs::read_to_string(path)
+            .wrap_err_with(|| format!("Failed to read the text from {}", path))?
+    };
+    Ok(strip_comments(text.trim_start_matches('\n')))
+}
+
+/**
+ * Splits the given text by the first empty line into a title and an optional description,
+ * similar to git commit messages.
+ */
+pub fn split_title(text: &str) -> (String, Option<String>) {
+    match text.split_once("\n\n") {
+        None => (String::from(text.trim_end_matches('\n')), None),
+        Some((title, description)) => (String::from(title), Some(String::from(description))),
+    }
+}
+
+/// like `split_title`, but fails with `AppError::Aborted` if the title is empty
+pub fn split_required_title(text: &str) -> Result<(String, Option<String>), Report> {
+    let (title, description) = split_title(text);
+    if title.trim().is_empty() {
+        return Err(AppError::Aborted(String::from("the title is empty")).into());
+    }
+    Ok((title, description))
+}
+
+/**
+ * Joins the given title and description to a text which can be split again using `split_title`.
+ */
+pub fn join_title(title: &str, description: &str) -> String {
+    if description.is_empty() {
+        format!("{}\n", title)
+    } else {
+        format!("{}\n\n{}", title, description)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn strips_only_the_appended_comments() {
+        let buffer = "Title\n\nSome text\n\n# Heading\n# keep this comment\n";
+        let edited = append_comments(buffer, &[String::from("Editing issue 1")]);
+        assert!(edited.contains("# Editing issue 1"));
+        assert_eq!(strip_comments(&edited), buffer);
+    }
+
+    #[test]
+    fn keeps_buffers_without_scissors_line() {
+        assert_eq!(
+            strip_comments("Title\n\n# Heading\n\n"),
+            "Title\n\n# Heading\n"
+        );
+        assert_eq!(strip_comments("\n\n"), "");
+    }
+
+    #[test]
+    fn strips_below_the_last_scissors_line() {
+        let buffer = format!("Title\n\n```\n{}\n```\n", SCISSORS_LINE);
+        let edited = append_comments(&buffer, &[]);
+        assert_eq!(strip_comments(&edited), buffer);
+        assert_eq!(strip_comments(&append_comments("", &[])), "");
+    }
+
+    #[test]
+    fn splits_title_by_first_empty_line() {
+        assert_eq!(split_title("Title\n"), (String::from("Title"), None));
+        assert_eq!(
+            split_title("Title\n\nFirst\n\nSecond\n"),
+            (
+                String::from("Title"),
+                Some(String::from("First\n\nSecond\n"))
+            )
+        );
+        assert_eq!(
+            split_title(&join_title("Title", "Text\n")),
+            (String::from("Title"), Some(String::from("Text\n")))
+        );
+    }
+
+    #[test]
+    fn requires_a_title() {
+        assert!(split_required_title("\n\nText\n").is_err());
+        assert!(split_required_title(&join_title("", "Text\n")).is_err());
+        assert!(split_required_title("  \n").is_err());
+        assert!(split_required_title("Title\n\nText\n").is_ok());
+    }
+
+    fn args(editor_command: &str) -> Vec<String> {
+        editor_args(editor_command, Path::new("/tmp/a b/issue.md"), 3).unwrap()
+    }
+
+    #[test]
+    fn appends_the_file_to_the_editor_command() {
+        assert_eq!(args("vim"), vec!["vim", "/tmp/a b/issue.md"]);
+        assert_eq!(
+            args("emacsclient -c -a ''"),
+            vec!["emacsclient", "-c", "-a", "", "/tmp/a b/issue.md"]
+        );
+        assert_eq!(
+            args("'/opt/My Editor/bin/edit' --wait"),
+            vec!["/opt/My Editor/bin/edit", "--wait", "/tmp/a b/issue.md"]
+        );
+    }
+
+    #[test]
+    fn replaces_placeholders_in_the_editor_command() {
+        assert_eq!(
+            args("code --wait --goto {file}:{line}"),
+            vec!["code", "--wait", "--goto", "/tmp/a b/issue.md:3"]
+        );
+        assert_eq!(args("vim +{line}"), vec!["vim", "+3", "/tmp/a b/issue.md"]);
+    }
+
+    #[test]
+    fn rejects_invalid_editor_commands() {
+        let path = Path::new("issue.md");
+        assert!(editor_args("", path, 1).is_err());
+        assert!(editor_args("  ", path, 1).is_err());
+        assert!(editor_args("vim 'unterminated", path, 1).is_err());
+    }
+}
@@ -4,6 +4,32 @@ use thiserror::Error;
 pub enum AppError {
     #[error("Not implemented: {0}.")]
     NotImplemented(String),
+    #[error("Aborted: {0}.")]
+    Aborted(String),
+    #[error("Unknown forge '{0}'.")]
+    UnknownForge(String),
+    #[error("Could not parse the git remote URL '{0}'.")]
+    InvalidRemoteUrl(String),
     #[error("{message}: {details}")]
     GraphqlError { message: String, details: String },
+    #[error("{message} (status {status}): {details}")]
+    RestError {
+        message: String,
+        status: u16,
+        details: String,
+    },
+}
+
+impl AppError {
+    /// returns a stable identifier of the kind of this error, e.g. 'rest_error', as reported in JSON output
+    pub fn kind(&self) -> &'static str {
+        match self {
+            AppError::NotImplemented(_) => "not_implemented",
+            AppError::Aborted(_) => "aborted",
+            AppError::UnknownForge(_) => "unknown_forge",
+            AppError::InvalidRemoteUrl(_) => "invalid_remote_url",
+            AppError::GraphqlError { .. } => "graphql_error",
+            AppError::RestError { .. } => "rest_error",
+        }
+    }
 }
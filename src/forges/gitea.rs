@@ -0,0 +1,694 @@
+use async_trait::async_trait;
+use eyre::{eyre, Report};
+use reqwest::{Client, Method, RequestBuilder};
+use serde::Deserialize;
+use serde_json::{json, Map, Value};
+
+use super::{
+    rest::{send, send_json},
+    split_project_path, CreatedIssue, CreatedMergeRequest, Discussion, Forge, Issue, IssueFilter,
+    IssueSummary, MergeRequest, MergeRequestFilter, MergeRequestState, MergeRequestSummary,
+    MergeRequestUpdate, Note,
+};
+
+/// number of items requested per page when searching or listing them. Instances may be configured to return
+/// less items per page, so only an empty page marks the end of a list.
+const PAGE_SIZE: usize = 50;
+
+/// title prefixes marking a pull request as work in progress, the first one is used for new drafts
+const DRAFT_PREFIXES: &[&str] = &["WIP: ", "WIP:", "[WIP] ", "[WIP]"];
+
+#[derive(Debug, Deserialize)]
+struct Branch {
+    #[serde(rename = "ref")]
+    name: String,
+    /// the repository of the branch, missing if it has been deleted
+    repo: Option<BranchRepository>,
+}
+
+#[derive(Debug, Deserialize)]
+struct BranchRepository {
+    owner: User,
+}
+
+#[derive(Debug, Deserialize)]
+struct PullRequest {
+    number: u64,
+    title: String,
+    body: Option<String>,
+    html_url: String,
+    head: Branch,
+    base: Branch,
+    #[serde(default)]
+    labels: Vec<Label>,
+    #[serde(default)]
+    assignees: Option<Vec<User>>,
+    #[serde(default)]
+    requested_reviewers: Option<Vec<User>>,
+    milestone: Option<Milestone>,
+    user: Option<User>,
+    #[serde(default)]
+    state: String,
+    #[serde(default)]
+    updated_at: String,
+    #[serde(default)]
+    merged: bool,
+}
+
+impl From<PullRequest> for MergeRequest {
+    fn from(pull: PullRequest) -> Self {
+        let (title, draft) = split_draft_prefix(&pull.title);
+        let logins = |users: Option<Vec<User>>| -> Vec<String> {
+            users
+                .unwrap_or_default()
+                .into_iter()
+                .map(|user| user.login)
+                .collect()
+        };
+        let state = if pull.merged {
+            "merged"
+        } else if pull.state == "open" {
+            "opened"
+        } else {
+            "closed"
+        };
+
+        MergeRequest {
+            iid: pull.number.to_string(),
+            title,
+            description: pull.body.unwrap_or_default(),
+            web_url: pull.html_url,
+            author: pull.user.map(|user| user.login).unwrap_or_default(),
+            state: String::from(state),
+            source_branch: pull.head.name,
+            target_branch: pull.base.name,
+            draft,
+            labels: pull.labels.into_iter().map(|label| label.name).collect(),
+            assignees: logins(pull.assignees),
+            reviewers: logins(pull.requested_reviewers),
+            milestone: pull.milestone.map(|milestone| milestone.title),
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct Comment {
+    user: Option<User>,
+    #[serde(default)]
+    body: String,
+    created_at: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct Label {
+    id: u64,
+    name: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct User {
+    login: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct Milestone {
+    id: u64,
+    title: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct Repository {
+    default_branch: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct CreatedPullRequest {
+    number: u64,
+    html_url: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct RemoteIssue {
+    number: u64,
+    title: String,
+    body: Option<String>,
+    html_url: String,
+    #[serde(default)]
+    state: String,
+    user: Option<User>,
+    #[serde(default)]
+    assignees: Option<Vec<User>>,
+    #[serde(default)]
+    labels: Vec<Label>,
+    milestone: Option<Milestone>,
+    due_date: Option<String>,
+    #[serde(default)]
+    updated_at: String,
+}
+
+/**
+ * Forge backend talking to the REST API of a Gitea or Forgejo instance.
+ */
+pub struct Gitea {
+    api_url: String,
+    token: String,
+    owner: String,
+    repo: String,
+    /// owner of the repository pull requests are opened from, i.e. of the fork if the repository is its upstream
+    source_owner: String,
+    client: Client,
+}
+
+impl Gitea {
+    /**
+     * Returns a new Gitea backend for the given project path, e.g. 'myusername/myproject'.
+     * If pull requests are opened from a fork of the repository, `fork_path` is the path of the fork.
+     * The API is expected to be served at '<host>/api/v1'.
+     */
+    pub fn new(
+        host: &str,
+        token: &str,
+        project_path: &str,
+        fork_path: Option<&str>,
+    ) -> Result<Gitea, Report> {
+        let (owner, repo) = split_project_path(project_path, "Gitea")?;
+        let source_owner = match fork_path {
+            Some(fork_path) => split_project_path(fork_path, "Gitea")?.0,
+            None => owner,
+        };
+
+        Ok(Gitea {
+            api_url: format!("{}/api/v1", host),
+            token: token.to_string(),
+            owner: owner.to_string(),
+            repo: repo.to_string(),
+            source_owner: source_owner.to_string(),
+            client: Client::new(),
+        })
+    }
+
+    /// returns a new request to the given path relative to the API endpoint of the repository,
+    /// an empty path refers to the repository itself
+    fn repo_request(&self, method: Method, path: &str) -> RequestBuilder {
+        self.client
+            .request(
+                method,
+                format!(
+                    "{}/repos/{}/{}/{}",
+                    self.api_url, self.owner, self.repo, path
+                )
+                .trim_end_matches('/'),
+            )
+            .header("Authorization", format!("token {}", self.token))
+            .header("Accept", "application/json")
+    }
+
+    /**
+     * Returns the pull requests of the given head branch in the given state.
+     * The Gitea API does not allow filtering pull requests by their head branch, so we page through them.
+     */
+    async fn find_pull_requests(
+        &self,
+        branch_name: &str,
+        state: MergeRequestState,
+    ) -> Result<Vec<MergeRequest>, Report> {
+        let mut mrs = Vec::new();
+        for page in 1.. {
+            let request = self.repo_request(Method::GET, "pulls").query(&[
+                ("state", String::from(state.rest_state())),
+                ("sort", String::from("recentupdate")),
+                ("page", page.to_string()),
+                ("limit", PAGE_SIZE.to_string()),
+            ]);
+            let pulls: Vec<PullRequest> = send_json(
+                request,
+                "Gitea returned an error while fetching the pull requests",
+            )
+            .await?;
+
+            if pulls.is_empty() {
+                break;
+            }
+            mrs.extend(
+                pulls
+                    .into_iter()
+                    .filter(|pull| {
+                        // pull requests of other forks may use the same branch name:
+                        pull.head.name == branch_name
+                            && pull.head.repo.as_ref().is_some_and(|repo| {
+                                repo.owner.login.eq_ignore_ascii_case(&self.source_owner)
+                            })
+                    })
+                    .map(MergeRequest::from),
+            );
+        }
+
+        Ok(mrs)
+    }
+
+    /**
+     * Returns the IDs of the repository labels with the given names,
+     * because the Gitea API does not accept label names when creating issues.
+     */
+    async fn get_label_ids(&self, names: &[String]) -> Result<Vec<u64>, Report> {
+        if names.is_empty() {
+            return Ok(vec![]);
+        }
+
+        let mut labels: Vec<Label> = vec![];
+        for page in 1.. {
+            let request = self
+                .repo_request(Method::GET, "labels")
+                .query(&[("page", page.to_string()), ("limit", PAGE_SIZE.to_string())]);
+            let page_labels: Vec<Label> =
+                send_json(request, "Gitea returned an error while fetching the labels").await?;
+
+            if page_labels.is_empty() {
+                break;
+            }
+            labels.extend(page_labels);
+        }
+
+        names
+            .iter()
+            .map(|name| {
+                labels
+                    .iter()
+                    .find(|label| &label.name == name)
+                    .map(|label| label.id)
+                    .ok_or_else(|| eyre!("Unknown label '{}'", name))
+            })
+            .collect()
+    }
+
+    /**
+     * Returns the ID of the milestone with the given title.
+     */
+    async fn get_milestone_id(&self, title: &str) -> Result<u64, Report> {
+        let request = self
+            .repo_request(Method::GET, "milestones")
+            .query(&[("state", "all"), ("name", title)]);
+        let milestones: Vec<Milestone> = send_json(
+            request,
+            "Gitea returned an error while fetching the milestones",
+        )
+        .await?;
+
+        milestones
+            .into_iter()
+            .find(|milestone| milestone.title == title)
+            .map(|milestone| milestone.id)
+            .ok_or_else(|| eyre!("Unknown milestone '{}'", title))
+    }
+
+    /**
+     * Requests reviews from the given users and removes all other pending review requests.
+     */
+    async fn set_reviewers(&self, mr: &MergeRequest, reviewers: &[String]) -> Result<(), Report> {
+        let path = format!("pulls/{}/requested_reviewers", mr.iid);
+        let added: Vec<&String> = reviewers
+            .iter()
+            .filter(|reviewer| !mr.reviewers.contains(reviewer))
+            .collect();
+        let removed: Vec<&String> = mr
+            .reviewers
+            .iter()
+            .filter(|reviewer| !reviewers.contains(reviewer))
+            .collect();
+
+        if !added.is_empty() {
+            let request = self
+                .repo_request(Method::POST, &path)
+                .json(&json!({ "reviewers": added }));
+            send(request, "Gitea returned an error while requesting reviews").await?;
+        }
+        if !removed.is_empty() {
+            let request = self
+                .repo_request(Method::DELETE, &path)
+                .json(&json!({ "reviewers": removed }));
+            send(
+                request,
+                "Gitea returned an error while removing review requests",
+            )
+            .await?;
+        }
+
+        Ok(())
+    }
+}
+
+/**
+ * Splits a pull request title into the title without draft prefix and whether the pull request is a draft.
+ * Gitea has no separate draft flag, but treats pull requests with a 'WIP' prefix as drafts.
+ */
+fn split_draft_prefix(title: &str) -> (String, bool) {
+    DRAFT_PREFIXES
+        .iter()
+        .find_map(|prefix| title.strip_prefix(prefix))
+        .map(|title| (title.to_string(), true))
+        .unwrap_or_else(|| (title.to_string(), false))
+}
+
+#[async_trait]
+impl Forge for Gitea {
+    async fn get_merge_requests(
+        &self,
+        branch_name: &str,
+        state: Option<MergeRequestState>,
+    ) -> Result<Vec<MergeRequest>, Report> {
+        match state {
+            Some(state) => self.find_pull_requests(branch_name, state).await,
+            // merged and closed pull requests are only searched if there is no open one,
+            // as they would not be chosen anyway:
+            None => {
+                let open = self
+                    .find_pull_requests(branch_name, MergeRequestState::Opened)
+                    .await?;
+                if open.is_empty() {
+                    self.find_pull_requests(branch_name, MergeRequestState::Closed)
+                        .await
+                } else {
+                    Ok(open)
+                }
+            }
+        }
+    }
+
+    async fn get_merge_request_by_iid(&self, iid: &str) -> Result<MergeRequest, Report> {
+        let request = self.repo_request(Method::GET, &format!("pulls/{}", iid));
+        let pull: PullRequest = send_json(
+            request,
+            "Gitea returned an error while fetching the pull request",
+        )
+        .await?;
+
+        Ok(MergeRequest::from(pull))
+    }
+
+    async fn get_merge_request_discussions(&self, iid: &str) -> Result<Vec<Discussion>, Report> {
+        // pull requests are issues as well, comments on the diff are only available per review
+        // and are not included:
+        self.get_issue_discussions(iid).await
+    }
+
+    async fn list_merge_requests(
+        &self,
+        filter: &MergeRequestFilter,
+    ) -> Result<Vec<MergeRequestSummary>, Report> {
+        let mut summaries = vec![];
+
+        // all filters but the state are applied locally,
+        // so we page through the pull requests until enough of them match:
+        for page in 1.. {
+            let request = self.repo_request(Method::GET, "pulls").query(&[
+                ("state", String::from(filter.rest_state())),
+                ("sort", String::from("recentupdate")),
+                ("page", page.to_string()),
+                ("limit", PAGE_SIZE.to_string()),
+            ]);
+            let pulls: Vec<PullRequest> = send_json(
+                request,
+                "Gitea returned an error while listing the pull requests",
+            )
+            .await?;
+            if pulls.is_empty() {
+                break;
+            }
+
+            for pull in pulls {
+                let updated_at = pull.updated_at.clone();
+                let mr = MergeRequest::from(pull);
+
+                if filter.matches(&mr) {
+                    summaries.push(MergeRequestSummary {
+                        iid: mr.iid,
+                        title: mr.title,
+                        author: mr.author,
+                        draft: mr.draft,
+                        pipeline_status: None,
+                        updated_at,
+                        web_url: mr.web_url,
+                    });
+                    if summaries.len() >= filter.limit {
+                        return Ok(summaries);
+                    }
+                }
+            }
+        }
+
+        Ok(summaries)
+    }
+
+    async fn update_merge_request(
+        &self,
+        mr: &MergeRequest,
+        update: &MergeRequestUpdate,
+    ) -> Result<(), Report> {
+        let mut fields = Map::new();
+        if update.title.is_some() || update.draft.is_some() {
+            let title = update.title.as_ref().unwrap_or(&mr.title);
+            let title = if update.draft.unwrap_or(mr.draft) {
+                format!("{}{}", DRAFT_PREFIXES[0], title)
+            } else {
+                title.clone()
+            };
+            fields.insert(String::from("title"), json!(title));
+        }
+        if let Some(description) = &update.description {
+            fields.insert(String::from("body"), json!(description));
+        }
+        if let Some(labels) = &update.labels {
+            fields.insert(
+                String::from("labels"),
+                json!(self.get_label_ids(labels).await?),
+            );
+        }
+        if let Some(assignees) = &update.assignees {
+            fields.insert(String::from("assignees"), json!(assignees));
+        }
+        if let Some(milestone) = &update.milestone {
+            // a milestone ID of 0 removes the milestone:
+            let id = match milestone {
+                Some(title) => self.get_milestone_id(title).await?,
+                None => 0,
+            };
+            fields.insert(String::from("milestone"), json!(id));
+        }
+
+        if !fields.is_empty() {
+            let request = self
+                .repo_request(Method::PATCH, &format!("pulls/{}", mr.iid))
+                .json(&Value::Object(fields));
+            send(
+                request,
+                "Gitea returned an error while updating the pull request",
+            )
+            .await?;
+        }
+
+        if let Some(reviewers) = &update.reviewers {
+            self.set_reviewers(mr, reviewers).await?;
+        }
+
+        Ok(())
+    }
+
+    async fn get_default_branch(&self) -> Result<String, Report> {
+        let request = self.repo_request(Method::GET, "");
+        let repository: Repository = send_json(
+            request,
+            "Gitea returned an error while fetching the repository details",
+        )
+        .await?;
+
+        Ok(repository.default_branch)
+    }
+
+    async fn create_merge_request(
+        &self,
+        source_branch: &str,
+        target_branch: &str,
+        title: &str,
+        description: &Option<String>,
+    ) -> Result<CreatedMergeRequest, Report> {
+        // branches of forks are referenced as '<owner>:<branch>':
+        let head = if self.source_owner == self.owner {
+            source_branch.to_string()
+        } else {
+            format!("{}:{}", self.source_owner, source_branch)
+        };
+        let request = self.repo_request(Method::POST, "pulls").json(&json!({
+            "title": title,
+            "body": description,
+            "head": head,
+            "base": target_branch,
+        }));
+        let pull: CreatedPullRequest = send_json(
+            request,
+            "Gitea returned an error while creating the pull request",
+        )
+        .await?;
+
+        Ok(CreatedMergeRequest {
+            iid: pull.number.to_string(),
+            web_url: pull.html_url,
+        })
+    }
+
+    async fn create_issue(
+        &self,
+        title: &str,
+        description: &Option<String>,
+        labels: &[String],
+    ) -> Result<CreatedIssue, Report> {
+        let label_ids = self.get_label_ids(labels).await?;
+        let request = self
+            .repo_request(Method::POST, "issues")
+            .json(&json!({ "title": title, "body": description, "labels": label_ids }));
+        let issue: RemoteIssue =
+            send_json(request, "Gitea returned an error while creating the issue").await?;
+
+        Ok(CreatedIssue {
+            iid: issue.number.to_string(),
+            web_url: issue.html_url,
+        })
+    }
+
+    async fn list_issues(&self, filter: &IssueFilter) -> Result<Vec<IssueSummary>, Report> {
+        // the issue API cannot be sorted, it lists the most recently created issues first:
+        let mut query = vec![
+            ("state", String::from(filter.rest_state())),
+            ("type", String::from("issues")),
+            ("limit", PAGE_SIZE.to_string()),
+        ];
+        if !filter.labels.is_empty() {
+            query.push(("labels", filter.labels.join(",")));
+        }
+        if let Some(milestone) = &filter.milestone {
+            query.push(("milestones", milestone.clone()));
+        }
+        if let Some(search) = &filter.search {
+            query.push(("q", search.clone()));
+        }
+
+        let mut summaries = vec![];
+        // assignees and due dates are matched locally, so we page through the issues until enough of them match:
+        for page in 1.. {
+            let request = self
+                .repo_request(Method::GET, "issues")
+                .query(&query)
+                .query(&[("page", page)]);
+            let issues: Vec<RemoteIssue> =
+                send_json(request, "Gitea returned an error while listing the issues").await?;
+            if issues.is_empty() {
+                break;
+            }
+
+            for issue in issues {
+                let summary = IssueSummary {
+                    iid: issue.number.to_string(),
+                    title: issue.title,
+                    state: String::from(if issue.state == "open" {
+                        "opened"
+                    } else {
+                        "closed"
+                    }),
+                    author: issue.user.map(|user| user.login).unwrap_or_default(),
+                    assignees: issue
+                        .assignees
+                        .unwrap_or_default()
+                        .into_iter()
+                        .map(|user| user.login)
+                        .collect(),
+                    labels: issue.labels.into_iter().map(|label| label.name).collect(),
+                    milestone: issue.milestone.map(|milestone| milestone.title),
+                    due_date: issue.due_date,
+                    confidential: false,
+                    updated_at: issue.updated_at,
+                    web_url: issue.html_url,
+                };
+
+                if filter.matches(&summary) {
+                    summaries.push(summary);
+                    if summaries.len() >= filter.limit {
+                        return Ok(summaries);
+                    }
+                }
+            }
+        }
+
+        Ok(summaries)
+    }
+
+    async fn get_issue(&self, iid: &str) -> Result<Issue, Report> {
+        let request = self.repo_request(Method::GET, &format!("issues/{}", iid));
+        let issue: RemoteIssue =
+            send_json(request, "Gitea returned an error while fetching the issue").await?;
+
+        Ok(Issue {
+            iid: issue.number.to_string(),
+            title: issue.title,
+            description: issue.body.unwrap_or_default(),
+            web_url: issue.html_url,
+            author: issue.user.map(|user| user.login).unwrap_or_default(),
+            state: String::from(if issue.state == "open" {
+                "opened"
+            } else {
+                "closed"
+            }),
+            labels: issue.labels.into_iter().map(|label| label.name).collect(),
+            assignees: issue
+                .assignees
+                .unwrap_or_default()
+                .into_iter()
+                .map(|user| user.login)
+                .collect(),
+            milestone: issue.milestone.map(|milestone| milestone.title),
+        })
+    }
+
+    async fn get_issue_discussions(&self, iid: &str) -> Result<Vec<Discussion>, Report> {
+        // the comments of an issue are not paginated and not threaded:
+        let request = self.repo_request(Method::GET, &format!("issues/{}/comments", iid));
+        let comments: Vec<Comment> = send_json(
+            request,
+            "Gitea returned an error while fetching the comments",
+        )
+        .await?;
+
+        Ok(comments
+            .into_iter()
+            .map(|comment| Discussion {
+                notes: vec![Note {
+                    author: comment.user.map(|user| user.login).unwrap_or_default(),
+                    body: comment.body,
+                    created_at: comment.created_at,
+                    position: None,
+                }],
+                resolved: None,
+            })
+            .collect())
+    }
+
+    async fn update_issue(
+        &self,
+        iid: &str,
+        title: &Option<String>,
+        description: &Option<String>,
+    ) -> Result<(), Report> {
+        let mut fields = Map::new();
+        if let Some(title) = title {
+            fields.insert(String::from("title"), json!(title));
+        }
+        if let Some(description) = description {
+            fields.insert(String::from("body"), json!(description));
+        }
+
+        let request = self
+            .repo_request(Method::PATCH, &format!("issues/{}", iid))
+            .json(&Value::Object(fields));
+        send(request, "Gitea returned an error while updating the issue").await?;
+
+        Ok(())
+    }
+}
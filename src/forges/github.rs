@@ -0,0 +1,666 @@
+use async_trait::async_trait;
+use eyre::{eyre, Report};
+use reqwest::{Client, Method, RequestBuilder};
+use serde::Deserialize;
+use serde_json::{json, Map, Value};
+
+use super::{
+    rest::{send, send_json, send_json_pages},
+    split_project_path, CreatedIssue, CreatedMergeRequest, Discussion, Forge, Issue, IssueFilter,
+    IssueSummary, MergeRequest, MergeRequestFilter, MergeRequestState, MergeRequestSummary,
+    MergeRequestUpdate, Note,
+};
+use crate::errors::AppError;
+
+/// number of pull requests, issues or comments fetched per page when listing them
+const PAGE_SIZE: usize = 100;
+
+#[derive(Debug, Deserialize)]
+struct PullRequest {
+    number: u64,
+    title: String,
+    body: Option<String>,
+    html_url: String,
+    #[serde(default)]
+    draft: bool,
+    #[serde(default)]
+    labels: Vec<Label>,
+    #[serde(default)]
+    assignees: Vec<User>,
+    #[serde(default)]
+    requested_reviewers: Vec<User>,
+    milestone: Option<Milestone>,
+    user: Option<User>,
+    #[serde(default)]
+    state: String,
+    #[serde(default)]
+    updated_at: String,
+    merged_at: Option<String>,
+    head: Option<Branch>,
+    base: Option<Branch>,
+}
+
+impl From<PullRequest> for MergeRequest {
+    fn from(pull: PullRequest) -> Self {
+        let state = if pull.merged_at.is_some() {
+            "merged"
+        } else if pull.state == "open" {
+            "opened"
+        } else {
+            "closed"
+        };
+
+        MergeRequest {
+            iid: pull.number.to_string(),
+            title: pull.title,
+            description: pull.body.unwrap_or_default(),
+            web_url: pull.html_url,
+            author: pull.user.map(|user| user.login).unwrap_or_default(),
+            state: String::from(state),
+            source_branch: pull.head.map(|head| head.name).unwrap_or_default(),
+            target_branch: pull.base.map(|base| base.name).unwrap_or_default(),
+            draft: pull.draft,
+            labels: pull.labels.into_iter().map(|label| label.name).collect(),
+            assignees: pull.assignees.into_iter().map(|user| user.login).collect(),
+            reviewers: pull
+                .requested_reviewers
+                .into_iter()
+                .map(|user| user.login)
+                .collect(),
+            milestone: pull.milestone.map(|milestone| milestone.title),
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct Branch {
+    #[serde(rename = "ref")]
+    name: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct Comment {
+    id: u64,
+    user: Option<User>,
+    #[serde(default)]
+    body: String,
+    created_at: String,
+    /// the file commented on, only set for review comments
+    path: Option<String>,
+    line: Option<u64>,
+    original_line: Option<u64>,
+    /// the id of the first comment of the thread, only set for replies to review comments
+    in_reply_to_id: Option<u64>,
+}
+
+impl From<Comment> for Note {
+    fn from(comment: Comment) -> Self {
+        let line = comment.line.or(comment.original_line);
+        Note {
+            author: comment.user.map(|user| user.login).unwrap_or_default(),
+            body: comment.body,
+            created_at: comment.created_at,
+            position: comment.path.map(|path| match line {
+                Some(line) => format!("{}:{}", path, line),
+                None => path,
+            }),
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct Label {
+    name: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct User {
+    login: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct Milestone {
+    number: u64,
+    title: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct Repository {
+    default_branch: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct CreatedPullRequest {
+    number: u64,
+    html_url: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct RemoteIssue {
+    number: u64,
+    title: String,
+    body: Option<String>,
+    html_url: String,
+    #[serde(default)]
+    state: String,
+    user: Option<User>,
+    #[serde(default)]
+    assignees: Vec<User>,
+    #[serde(default)]
+    labels: Vec<Label>,
+    milestone: Option<Milestone>,
+    #[serde(default)]
+    updated_at: String,
+    /// only set if the issue is a pull request
+    pull_request: Option<Value>,
+}
+
+/**
+ * Forge backend talking to the REST API of Github or a Github Enterprise instance.
+ */
+pub struct Github {
+    api_url: String,
+    token: String,
+    owner: String,
+    repo: String,
+    /// owner of the repository pull requests are opened from, i.e. of the fork if the repository is its upstream
+    source_owner: String,
+    client: Client,
+}
+
+impl Github {
+    /**
+     * Returns a new Github backend for the given project path, e.g. 'myusername/myproject'.
+     * If pull requests are opened from a fork of the repository, `fork_path` is the path of the fork.
+     * github.com is served by 'api.github.com', all other hosts are assumed to be Github Enterprise
+     * instances serving the API at '<host>/api/v3'.
+     */
+    pub fn new(
+        host: &str,
+        token: &str,
+        project_path: &str,
+        fork_path: Option<&str>,
+    ) -> Result<Github, Report> {
+        let (owner, repo) = split_project_path(project_path, "Github")?;
+        let source_owner = match fork_path {
+            Some(fork_path) => split_project_path(fork_path, "Github")?.0,
+            None => owner,
+        };
+        let api_url = match host {
+            "https://github.com" | "http://github.com" => String::from("https://api.github.com"),
+            _ => format!("{}/api/v3", host),
+        };
+
+        Ok(Github {
+            api_url,
+            token: token.to_string(),
+            owner: owner.to_string(),
+            repo: repo.to_string(),
+            source_owner: source_owner.to_string(),
+            client: Client::new(),
+        })
+    }
+
+    /// returns a new request to the given path relative to the API endpoint of the repository,
+    /// an empty path refers to the repository itself
+    fn repo_request(&self, method: Method, path: &str) -> RequestBuilder {
+        self.client
+            .request(
+                method,
+                format!(
+                    "{}/repos/{}/{}/{}",
+                    self.api_url, self.owner, self.repo, path
+                )
+                .trim_end_matches('/'),
+            )
+            .header("Authorization", format!("Bearer {}", self.token))
+            .header("Accept", "application/vnd.github.v3+json")
+            .header("User-Agent", "forge-cli")
+    }
+
+    /**
+     * Returns the number of the milestone with the given title.
+     */
+    async fn get_milestone_number(&self, title: &str) -> Result<u64, Report> {
+        let request = self
+            .repo_request(Method::GET, "milestones")
+            .query(&[("state", "all"), ("per_page", "100")]);
+        let milestones: Vec<Milestone> = send_json(
+            request,
+            "Github returned an error while fetching the milestones",
+        )
+        .await?;
+
+        milestones
+            .into_iter()
+            .find(|milestone| milestone.title == title)
+            .map(|milestone| milestone.number)
+            .ok_or_else(|| eyre!("Unknown milestone '{}'", title))
+    }
+
+    /// fetches all comments of the given list, e.g. 'issues/1/comments', oldest first
+    async fn get_comments(&self, path: &str) -> Result<Vec<Comment>, Report> {
+        send_json_pages(
+            |page| {
+                self.repo_request(Method::GET, path)
+                    .query(&[("page", page), ("per_page", PAGE_SIZE)])
+            },
+            PAGE_SIZE,
+            "Github returned an error while fetching the comments",
+        )
+        .await
+    }
+
+    /**
+     * Requests reviews from the given users and removes all other pending review requests.
+     */
+    async fn set_reviewers(&self, mr: &MergeRequest, reviewers: &[String]) -> Result<(), Report> {
+        let path = format!("pulls/{}/requested_reviewers", mr.iid);
+        let added: Vec<&String> = reviewers
+            .iter()
+            .filter(|reviewer| !mr.reviewers.contains(reviewer))
+            .collect();
+        let removed: Vec<&String> = mr
+            .reviewers
+            .iter()
+            .filter(|reviewer| !reviewers.contains(reviewer))
+            .collect();
+
+        if !added.is_empty() {
+            let request = self
+                .repo_request(Method::POST, &path)
+                .json(&json!({ "reviewers": added }));
+            send(request, "Github returned an error while requesting reviews").await?;
+        }
+        if !removed.is_empty() {
+            let request = self
+                .repo_request(Method::DELETE, &path)
+                .json(&json!({ "reviewers": removed }));
+            send(
+                request,
+                "Github returned an error while removing review requests",
+            )
+            .await?;
+        }
+
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl Forge for Github {
+    async fn get_merge_requests(
+        &self,
+        branch_name: &str,
+        _state: Option<MergeRequestState>,
+    ) -> Result<Vec<MergeRequest>, Report> {
+        let request = self.repo_request(Method::GET, "pulls").query(&[
+            ("head", format!("{}:{}", self.source_owner, branch_name)),
+            ("state", String::from("all")),
+            ("per_page", PAGE_SIZE.to_string()),
+        ]);
+        let pulls: Vec<PullRequest> = send_json(
+            request,
+            "Github returned an error while fetching the pull requests",
+        )
+        .await?;
+
+        Ok(pulls.into_iter().map(MergeRequest::from).collect())
+    }
+
+    async fn get_merge_request_by_iid(&self, iid: &str) -> Result<MergeRequest, Report> {
+        let request = self.repo_request(Method::GET, &format!("pulls/{}", iid));
+        let pull: PullRequest = send_json(
+            request,
+            "Github returned an error while fetching the pull request",
+        )
+        .await?;
+
+        Ok(MergeRequest::from(pull))
+    }
+
+    async fn get_merge_request_discussions(&self, iid: &str) -> Result<Vec<Discussion>, Report> {
+        // comments on the conversation tab are not threaded:
+        let mut discussions: Vec<Discussion> = self.get_issue_discussions(iid).await?;
+
+        // replies to review comments refer to the first comment of their thread:
+        let mut threads: Vec<(u64, Discussion)> = vec![];
+        for comment in self
+            .get_comments(&format!("pulls/{}/comments", iid))
+            .await?
+        {
+            let thread = comment
+                .in_reply_to_id
+                .and_then(|id| threads.iter_mut().find(|(thread_id, _)| *thread_id == id));
+            match thread {
+                Some((_, thread)) => thread.notes.push(Note::from(comment)),
+                None => threads.push((
+                    comment.id,
+                    Discussion {
+                        notes: vec![Note::from(comment)],
+                        resolved: None,
+                    },
+                )),
+            }
+        }
+        discussions.extend(threads.into_iter().map(|(_, thread)| thread));
+        discussions.sort_by(|a, b| a.notes[0].created_at.cmp(&b.notes[0].created_at));
+
+        Ok(discussions)
+    }
+
+    async fn list_merge_requests(
+        &self,
+        filter: &MergeRequestFilter,
+    ) -> Result<Vec<MergeRequestSummary>, Report> {
+        let mut summaries = vec![];
+
+        // all filters but the state and target branch are applied locally,
+        // so we page through the pull requests until enough of them match:
+        for page in 1.. {
+            let mut query = vec![
+                ("state", String::from(filter.rest_state())),
+                ("sort", String::from("updated")),
+                ("direction", String::from("desc")),
+                ("page", page.to_string()),
+                ("per_page", PAGE_SIZE.to_string()),
+            ];
+            if let Some(target_branch) = &filter.target_branch {
+                query.push(("base", target_branch.clone()));
+            }
+            let request = self.repo_request(Method::GET, "pulls").query(&query);
+            let pulls: Vec<PullRequest> = send_json(
+                request,
+                "Github returned an error while listing the pull requests",
+            )
+            .await?;
+            let last_page = pulls.len() < PAGE_SIZE;
+
+            for pull in pulls {
+                let updated_at = pull.updated_at.clone();
+                let mr = MergeRequest::from(pull);
+
+                if filter.matches(&mr) {
+                    summaries.push(MergeRequestSummary {
+                        iid: mr.iid,
+                        title: mr.title,
+                        author: mr.author,
+                        draft: mr.draft,
+                        pipeline_status: None,
+                        updated_at,
+                        web_url: mr.web_url,
+                    });
+                    if summaries.len() >= filter.limit {
+                        return Ok(summaries);
+                    }
+                }
+            }
+            if last_page {
+                break;
+            }
+        }
+
+        Ok(summaries)
+    }
+
+    async fn update_merge_request(
+        &self,
+        mr: &MergeRequest,
+        update: &MergeRequestUpdate,
+    ) -> Result<(), Report> {
+        if update.draft.is_some() {
+            // the REST API does not allow to convert pull requests from or to drafts:
+            return Err(AppError::NotImplemented(String::from(
+                "changing the draft status of Github pull requests",
+            ))
+            .into());
+        }
+
+        let mut pull_fields = Map::new();
+        if let Some(title) = &update.title {
+            pull_fields.insert(String::from("title"), json!(title));
+        }
+        if let Some(description) = &update.description {
+            pull_fields.insert(String::from("body"), json!(description));
+        }
+        if !pull_fields.is_empty() {
+            let request = self
+                .repo_request(Method::PATCH, &format!("pulls/{}", mr.iid))
+                .json(&Value::Object(pull_fields));
+            send(
+                request,
+                "Github returned an error while updating the pull request",
+            )
+            .await?;
+        }
+
+        // labels, assignees and milestones are managed via the issue API:
+        let mut issue_fields = Map::new();
+        if let Some(labels) = &update.labels {
+            issue_fields.insert(String::from("labels"), json!(labels));
+        }
+        if let Some(assignees) = &update.assignees {
+            issue_fields.insert(String::from("assignees"), json!(assignees));
+        }
+        if let Some(milestone) = &update.milestone {
+            let number = match milestone {
+                Some(title) => Some(self.get_milestone_number(title).await?),
+                None => None,
+            };
+            issue_fields.insert(String::from("milestone"), json!(number));
+        }
+        if !issue_fields.is_empty() {
+            let request = self
+                .repo_request(Method::PATCH, &format!("issues/{}", mr.iid))
+                .json(&Value::Object(issue_fields));
+            send(
+                request,
+                "Github returned an error while updating the pull request",
+            )
+            .await?;
+        }
+
+        if let Some(reviewers) = &update.reviewers {
+            self.set_reviewers(mr, reviewers).await?;
+        }
+
+        Ok(())
+    }
+
+    async fn get_default_branch(&self) -> Result<String, Report> {
+        let request = self.repo_request(Method::GET, "");
+        let repository: Repository = send_json(
+            request,
+            "Github returned an error while fetching the repository details",
+        )
+        .await?;
+
+        Ok(repository.default_branch)
+    }
+
+    async fn create_merge_request(
+        &self,
+        source_branch: &str,
+        target_branch: &str,
+        title: &str,
+        description: &Option<String>,
+    ) -> Result<CreatedMergeRequest, Report> {
+        // branches of forks are referenced as '<owner>:<branch>':
+        let head = if self.source_owner == self.owner {
+            source_branch.to_string()
+        } else {
+            format!("{}:{}", self.source_owner, source_branch)
+        };
+        let request = self.repo_request(Method::POST, "pulls").json(&json!({
+            "title": title,
+            "body": description,
+            "head": head,
+            "base": target_branch,
+        }));
+        let pull: CreatedPullRequest = send_json(
+            request,
+            "Github returned an error while creating the pull request",
+        )
+        .await?;
+
+        Ok(CreatedMergeRequest {
+            iid: pull.number.to_string(),
+            web_url: pull.html_url,
+        })
+    }
+
+    async fn create_issue(
+        &self,
+        title: &str,
+        description: &Option<String>,
+        labels: &[String],
+    ) -> Result<CreatedIssue, Report> {
+        let request = self
+            .repo_request(Method::POST, "issues")
+            .json(&json!({ "title": title, "body": description, "labels": labels }));
+        let issue: RemoteIssue =
+            send_json(request, "Github returned an error while creating the issue").await?;
+
+        Ok(CreatedIssue {
+            iid: issue.number.to_string(),
+            web_url: issue.html_url,
+        })
+    }
+
+    async fn list_issues(&self, filter: &IssueFilter) -> Result<Vec<IssueSummary>, Report> {
+        if filter.has_due_date_range() {
+            return Err(
+                AppError::NotImplemented(String::from("due dates of Github issues")).into(),
+            );
+        }
+
+        let mut query = vec![
+            ("state", String::from(filter.rest_state())),
+            ("sort", String::from("updated")),
+            ("direction", String::from("desc")),
+            ("per_page", PAGE_SIZE.to_string()),
+        ];
+        if !filter.labels.is_empty() {
+            query.push(("labels", filter.labels.join(",")));
+        }
+        if let Some(assignee) = &filter.assignee {
+            query.push(("assignee", assignee.clone()));
+        }
+        if let Some(milestone) = &filter.milestone {
+            let number = self.get_milestone_number(milestone).await?;
+            query.push(("milestone", number.to_string()));
+        }
+        let search = filter.search.as_ref().map(|search| search.to_lowercase());
+
+        let mut summaries = vec![];
+        // the search text is matched locally, so we page through the issues until enough of them match:
+        for page in 1.. {
+            let request = self
+                .repo_request(Method::GET, "issues")
+                .query(&query)
+                .query(&[("page", page)]);
+            let issues: Vec<RemoteIssue> =
+                send_json(request, "Github returned an error while listing the issues").await?;
+            let last_page = issues.len() < PAGE_SIZE;
+
+            // the issue API lists pull requests as well:
+            for issue in issues
+                .into_iter()
+                .filter(|issue| issue.pull_request.is_none())
+            {
+                let found = search.as_ref().is_none_or(|search| {
+                    issue.title.to_lowercase().contains(search)
+                        || issue
+                            .body
+                            .as_ref()
+                            .is_some_and(|body| body.to_lowercase().contains(search))
+                });
+                let summary = IssueSummary {
+                    iid: issue.number.to_string(),
+                    title: issue.title,
+                    state: String::from(if issue.state == "open" {
+                        "opened"
+                    } else {
+                        "closed"
+                    }),
+                    author: issue.user.map(|user| user.login).unwrap_or_default(),
+                    assignees: issue.assignees.into_iter().map(|user| user.login).collect(),
+                    labels: issue.labels.into_iter().map(|label| label.name).collect(),
+                    milestone: issue.milestone.map(|milestone| milestone.title),
+                    due_date: None,
+                    confidential: false,
+                    updated_at: issue.updated_at,
+                    web_url: issue.html_url,
+                };
+
+                if found && filter.matches(&summary) {
+                    summaries.push(summary);
+                    if summaries.len() >= filter.limit {
+                        return Ok(summaries);
+                    }
+                }
+            }
+            if last_page {
+                break;
+            }
+        }
+
+        Ok(summaries)
+    }
+
+    async fn get_issue(&self, iid: &str) -> Result<Issue, Report> {
+        let request = self.repo_request(Method::GET, &format!("issues/{}", iid));
+        let issue: RemoteIssue =
+            send_json(request, "Github returned an error while fetching the issue").await?;
+
+        Ok(Issue {
+            iid: issue.number.to_string(),
+            title: issue.title,
+            description: issue.body.unwrap_or_default(),
+            web_url: issue.html_url,
+            author: issue.user.map(|user| user.login).unwrap_or_default(),
+            state: String::from(if issue.state == "open" {
+                "opened"
+            } else {
+                "closed"
+            }),
+            labels: issue.labels.into_iter().map(|label| label.name).collect(),
+            assignees: issue.assignees.into_iter().map(|user| user.login).collect(),
+            milestone: issue.milestone.map(|milestone| milestone.title),
+        })
+    }
+
+    async fn get_issue_discussions(&self, iid: &str) -> Result<Vec<Discussion>, Report> {
+        Ok(self
+            .get_comments(&format!("issues/{}/comments", iid))
+            .await?
+            .into_iter()
+            .map(|comment| Discussion {
+                notes: vec![Note::from(comment)],
+                resolved: None,
+            })
+            .collect())
+    }
+
+    async fn update_issue(
+        &self,
+        iid: &str,
+        title: &Option<String>,
+        description: &Option<String>,
+    ) -> Result<(), Report> {
+        let mut fields = Map::new();
+        if let Some(title) = title {
+            fields.insert(String::from("title"), json!(title));
+        }
+        if let Some(description) = description {
+            fields.insert(String::from("body"), json!(description));
+        }
+
+        let request = self
+            .repo_request(Method::PATCH, &format!("issues/{}", iid))
+            .json(&Value::Object(fields));
+        send(request, "Github returned an error while updating the issue").await?;
+
+        Ok(())
+    }
+}
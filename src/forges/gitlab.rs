@@ -0,0 +1,429 @@
+use async_trait::async_trait;
+use eyre::{eyre, Report};
+use reqwest::{Client, Method, RequestBuilder};
+use serde::Deserialize;
+use serde_json::json;
+
+use super::{
+    rest::{send, send_json},
+    CreatedIssue, CreatedMergeRequest, Discussion, Forge, Issue, IssueFilter, IssueSummary,
+    MergeRequest, MergeRequestFilter, MergeRequestState, MergeRequestSummary, MergeRequestUpdate,
+};
+use crate::queries::{
+    gitlab_create_issue::create_issue,
+    gitlab_create_mr::create_merge_request,
+    gitlab_get_default_branch::get_default_branch,
+    gitlab_get_discussions::{get_issue_discussions, get_mr_discussions},
+    gitlab_get_issue::get_issue,
+    gitlab_get_labels::get_label_ids,
+    gitlab_get_milestones::get_milestone_id,
+    gitlab_get_mr::{get_merge_request_by_iid, get_merge_requests, FetchedMergeRequest},
+    gitlab_list_issues::list_issues_page,
+    gitlab_list_mrs::list_mrs_page,
+    gitlab_update_issue::update_issue,
+    gitlab_update_mr::{
+        set_merge_request_assignees, set_merge_request_draft, set_merge_request_labels,
+        set_merge_request_milestone, update_merge_request,
+    },
+};
+
+#[derive(Debug, Deserialize)]
+struct User {
+    id: u64,
+}
+
+#[derive(Debug, Deserialize)]
+struct Project {
+    id: u64,
+}
+
+#[derive(Debug, Deserialize)]
+struct CreatedRestMergeRequest {
+    iid: u64,
+    web_url: String,
+}
+
+/**
+ * Forge backend talking to the Graphql API of a Gitlab instance.
+ */
+pub struct Gitlab {
+    host: String,
+    token: String,
+    project_path: String,
+    /// path of the project merge requests are opened from, i.e. the fork if `project_path` is its upstream
+    source_project_path: String,
+}
+
+impl Gitlab {
+    /**
+     * Returns a new Gitlab backend for the given project. If merge requests are opened from a fork
+     * of the project, `fork_path` is the path of the fork.
+     */
+    pub fn new(host: &str, token: &str, project_path: &str, fork_path: Option<&str>) -> Gitlab {
+        Gitlab {
+            host: host.to_string(),
+            token: token.to_string(),
+            project_path: project_path.to_string(),
+            source_project_path: fork_path.unwrap_or(project_path).to_string(),
+        }
+    }
+
+    /// returns whether merge requests are opened from a fork of the project
+    fn is_fork(&self) -> bool {
+        self.source_project_path != self.project_path
+    }
+
+    /// returns a new request to the given path relative to the REST API endpoint of the Gitlab instance
+    fn rest_request(&self, method: Method, path: &str) -> RequestBuilder {
+        Client::new()
+            .request(method, format!("{}/api/v4/{}", self.host, path))
+            .header("Authorization", format!("Bearer {}", self.token))
+    }
+
+    /**
+     * Returns the ID of the user with the given username.
+     */
+    async fn get_user_id(&self, username: &str) -> Result<u64, Report> {
+        let request = self
+            .rest_request(Method::GET, "users")
+            .query(&[("username", username)]);
+        let users: Vec<User> =
+            send_json(request, "Gitlab returned an error while fetching the user").await?;
+
+        users
+            .into_iter()
+            .next()
+            .map(|user| user.id)
+            .ok_or_else(|| eyre!("Unknown user '{}'", username))
+    }
+
+    /**
+     * Replaces the reviewers of the merge request with the given iid.
+     * The Graphql API does not offer a mutation for this, so the REST API is used instead.
+     */
+    async fn set_reviewers(&self, iid: &str, usernames: &[String]) -> Result<(), Report> {
+        let mut reviewer_ids = vec![];
+        for username in usernames {
+            reviewer_ids.push(self.get_user_id(username).await?);
+        }
+
+        let request = self
+            .rest_request(
+                Method::PUT,
+                &format!(
+                    "projects/{}/merge_requests/{}",
+                    self.project_path.replace('/', "%2F"),
+                    iid
+                ),
+            )
+            .json(&json!({ "reviewer_ids": reviewer_ids }));
+        send(
+            request,
+            "Gitlab returned an error while setting the merge request reviewers",
+        )
+        .await?;
+
+        Ok(())
+    }
+
+    /**
+     * Creates a merge request from the fork into the project.
+     * The Graphql API can only create merge requests within a single project, so the REST API is used instead.
+     */
+    async fn create_fork_merge_request(
+        &self,
+        source_branch: &str,
+        target_branch: &str,
+        title: &str,
+        description: &Option<String>,
+    ) -> Result<CreatedMergeRequest, Report> {
+        let request = self.rest_request(
+            Method::GET,
+            &format!("projects/{}", self.project_path.replace('/', "%2F")),
+        );
+        let target_project: Project = send_json(
+            request,
+            "Gitlab returned an error while fetching the upstream project",
+        )
+        .await?;
+
+        let request = self
+            .rest_request(
+                Method::POST,
+                &format!(
+                    "projects/{}/merge_requests",
+                    self.source_project_path.replace('/', "%2F")
+                ),
+            )
+            .json(&json!({
+                "source_branch": source_branch,
+                "target_branch": target_branch,
+                "target_project_id": target_project.id,
+                "title": title,
+                "description": description,
+            }));
+        let mr: CreatedRestMergeRequest = send_json(
+            request,
+            "Gitlab returned an error while creating the merge request",
+        )
+        .await?;
+
+        Ok(CreatedMergeRequest {
+            iid: mr.iid.to_string(),
+            web_url: mr.web_url,
+        })
+    }
+
+    /// converts the given merge request fetched from the Graphql API
+    fn to_merge_request(&self, mr: FetchedMergeRequest) -> MergeRequest {
+        // older Gitlab instances may not return a web URL, in which case we build it ourselves:
+        let web_url = if mr.web_url.is_empty() {
+            format!(
+                "{}/{}/-/merge_requests/{}",
+                self.host, mr.project_path, mr.iid
+            )
+        } else {
+            mr.web_url
+        };
+
+        MergeRequest {
+            iid: mr.iid,
+            title: mr.title,
+            description: mr.description,
+            web_url,
+            author: mr.author,
+            state: mr.state,
+            source_branch: mr.source_branch,
+            target_branch: mr.target_branch,
+            draft: mr.draft,
+            labels: mr.labels,
+            assignees: mr.assignees,
+            reviewers: mr.reviewers,
+            milestone: mr.milestone,
+        }
+    }
+}
+
+#[async_trait]
+impl Forge for Gitlab {
+    async fn get_merge_requests(
+        &self,
+        branch_name: &str,
+        _state: Option<MergeRequestState>,
+    ) -> Result<Vec<MergeRequest>, Report> {
+        let mrs =
+            get_merge_requests(&self.host, &self.token, &self.project_path, branch_name).await?;
+
+        // merge requests of other forks may use the same branch name. Without a fork, all of them are kept,
+        // as the source project is missing if it is not accessible, e.g. if it has been deleted:
+        Ok(mrs
+            .into_iter()
+            .filter(|mr| {
+                !self.is_fork()
+                    || mr
+                        .source_project_path
+                        .as_ref()
+                        .is_some_and(|path| path.eq_ignore_ascii_case(&self.source_project_path))
+            })
+            .map(|mr| self.to_merge_request(mr))
+            .collect())
+    }
+
+    async fn get_merge_request_by_iid(&self, iid: &str) -> Result<MergeRequest, Report> {
+        let mr = get_merge_request_by_iid(&self.host, &self.token, &self.project_path, iid).await?;
+
+        Ok(self.to_merge_request(mr))
+    }
+
+    async fn get_merge_request_discussions(&self, iid: &str) -> Result<Vec<Discussion>, Report> {
+        get_mr_discussions(&self.host, &self.token, &self.project_path, iid).await
+    }
+
+    async fn list_merge_requests(
+        &self,
+        filter: &MergeRequestFilter,
+    ) -> Result<Vec<MergeRequestSummary>, Report> {
+        let mut mrs = vec![];
+        let mut cursor = None;
+
+        // Gitlab cannot filter by draft status,
+        // so we page through the merge requests until enough of them match:
+        loop {
+            let page =
+                list_mrs_page(&self.host, &self.token, &self.project_path, filter, cursor).await?;
+            for mr in page.merge_requests {
+                if filter.draft.is_none_or(|draft| draft == mr.draft) {
+                    mrs.push(mr);
+                    if mrs.len() >= filter.limit {
+                        return Ok(mrs);
+                    }
+                }
+            }
+            match page.next_cursor {
+                Some(next_cursor) => cursor = Some(next_cursor),
+                None => return Ok(mrs),
+            }
+        }
+    }
+
+    async fn update_merge_request(
+        &self,
+        mr: &MergeRequest,
+        update: &MergeRequestUpdate,
+    ) -> Result<(), Report> {
+        let (host, token, project_path, iid) =
+            (&self.host, &self.token, &self.project_path, &mr.iid);
+
+        if update.title.is_some() || update.description.is_some() {
+            update_merge_request(
+                host,
+                token,
+                project_path,
+                iid,
+                &update.title,
+                &update.description,
+            )
+            .await?;
+        }
+        if let Some(labels) = &update.labels {
+            let label_ids = get_label_ids(host, token, project_path, labels).await?;
+            set_merge_request_labels(host, token, project_path, iid, &label_ids).await?;
+        }
+        if let Some(assignees) = &update.assignees {
+            set_merge_request_assignees(host, token, project_path, iid, assignees).await?;
+        }
+        if let Some(reviewers) = &update.reviewers {
+            self.set_reviewers(iid, reviewers).await?;
+        }
+        if let Some(milestone) = &update.milestone {
+            let milestone_id = match milestone {
+                Some(title) => Some(get_milestone_id(host, token, project_path, title).await?),
+                None => None,
+            };
+            set_merge_request_milestone(host, token, project_path, iid, &milestone_id).await?;
+        }
+        if let Some(draft) = update.draft {
+            set_merge_request_draft(host, token, project_path, iid, draft).await?;
+        }
+
+        Ok(())
+    }
+
+    async fn get_default_branch(&self) -> Result<String, Report> {
+        get_default_branch(&self.host, &self.token, &self.project_path).await
+    }
+
+    async fn create_merge_request(
+        &self,
+        source_branch: &str,
+        target_branch: &str,
+        title: &str,
+        description: &Option<String>,
+    ) -> Result<CreatedMergeRequest, Report> {
+        if self.is_fork() {
+            return self
+                .create_fork_merge_request(source_branch, target_branch, title, description)
+                .await;
+        }
+
+        let mr = create_merge_request(
+            &self.host,
+            &self.token,
+            &self.project_path,
+            source_branch,
+            target_branch,
+            title,
+            description,
+        )
+        .await?;
+
+        Ok(CreatedMergeRequest {
+            iid: mr.iid,
+            web_url: mr.web_url,
+        })
+    }
+
+    async fn create_issue(
+        &self,
+        title: &str,
+        description: &Option<String>,
+        labels: &[String],
+    ) -> Result<CreatedIssue, Report> {
+        let issue = create_issue(
+            &self.host,
+            &self.token,
+            &self.project_path,
+            title,
+            description,
+            labels,
+        )
+        .await?;
+
+        Ok(CreatedIssue {
+            iid: issue.iid,
+            web_url: issue.web_url,
+        })
+    }
+
+    async fn list_issues(&self, filter: &IssueFilter) -> Result<Vec<IssueSummary>, Report> {
+        let mut issues = vec![];
+        let mut cursor = None;
+
+        // Gitlab cannot filter by confidentiality and due dates,
+        // so we page through the issues until enough of them match:
+        loop {
+            let page =
+                list_issues_page(&self.host, &self.token, &self.project_path, filter, cursor)
+                    .await?;
+            for issue in page.issues {
+                if filter.matches(&issue) {
+                    issues.push(issue);
+                    if issues.len() >= filter.limit {
+                        return Ok(issues);
+                    }
+                }
+            }
+            match page.next_cursor {
+                Some(next_cursor) => cursor = Some(next_cursor),
+                None => return Ok(issues),
+            }
+        }
+    }
+
+    async fn get_issue(&self, iid: &str) -> Result<Issue, Report> {
+        let issue = get_issue(&self.host, &self.token, &self.project_path, iid).await?;
+
+        Ok(Issue {
+            iid: issue.iid,
+            title: issue.title,
+            description: issue.description,
+            web_url: issue.web_url,
+            author: issue.author,
+            state: issue.state,
+            labels: issue.labels,
+            assignees: issue.assignees,
+            milestone: issue.milestone,
+        })
+    }
+
+    async fn get_issue_discussions(&self, iid: &str) -> Result<Vec<Discussion>, Report> {
+        get_issue_discussions(&self.host, &self.token, &self.project_path, iid).await
+    }
+
+    async fn update_issue(
+        &self,
+        iid: &str,
+        title: &Option<String>,
+        description: &Option<String>,
+    ) -> Result<(), Report> {
+        update_issue(
+            &self.host,
+            &self.token,
+            &self.project_path,
+            iid,
+            title,
+            description,
+        )
+        .await
+    }
+}
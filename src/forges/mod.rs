@@ -0,0 +1,757 @@
+use async_trait::async_trait;
+use eyre::{eyre, Report};
+use serde::Serialize;
+use std::str::FromStr;
+
+use crate::{cli::GlobalArgs, errors::AppError};
+
+pub mod gitea;
+pub mod github;
+pub mod gitlab;
+mod rest;
+
+/**
+ * A merge request (or pull request) as returned by a forge.
+ */
+#[derive(Debug, Serialize)]
+pub struct MergeRequest {
+    pub iid: String,
+    pub title: String,
+    pub description: String,
+    pub web_url: String,
+    /// username of the author
+    pub author: String,
+    /// either 'opened', 'merged' or 'closed'
+    pub state: String,
+    pub source_branch: String,
+    pub target_branch: String,
+    pub draft: bool,
+    pub labels: Vec<String>,
+    /// usernames of the assigned users
+    pub assignees: Vec<String>,
+    /// usernames of the requested reviewers
+    pub reviewers: Vec<String>,
+    /// title of the milestone, if any
+    pub milestone: Option<String>,
+}
+
+/**
+ * The changes to apply to a merge request. Fields set to `None` are left unchanged.
+ */
+#[derive(Debug, Default)]
+pub struct MergeRequestUpdate {
+    pub title: Option<String>,
+    pub description: Option<String>,
+    pub draft: Option<bool>,
+    pub labels: Option<Vec<String>>,
+    pub assignees: Option<Vec<String>>,
+    pub reviewers: Option<Vec<String>>,
+    /// the new milestone title, `Some(None)` removes the milestone
+    pub milestone: Option<Option<String>>,
+}
+
+impl MergeRequestUpdate {
+    /// returns whether this update does not change anything
+    pub fn is_empty(&self) -> bool {
+        self.title.is_none()
+            && self.description.is_none()
+            && self.draft.is_none()
+            && self.labels.is_none()
+            && self.assignees.is_none()
+            && self.reviewers.is_none()
+            && self.milestone.is_none()
+    }
+}
+
+/**
+ * The states merge requests can be filtered by.
+ */
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum MergeRequestState {
+    Opened,
+    Merged,
+    Closed,
+    All,
+}
+
+/// names of all merge request states, as accepted by `MergeRequestState::from_str`
+pub const MERGE_REQUEST_STATES: &[&str] = &["opened", "merged", "closed", "all"];
+
+impl FromStr for MergeRequestState {
+    type Err = Report;
+
+    fn from_str(name: &str) -> Result<Self, Self::Err> {
+        match name {
+            "opened" | "open" => Ok(MergeRequestState::Opened),
+            "merged" => Ok(MergeRequestState::Merged),
+            "closed" => Ok(MergeRequestState::Closed),
+            "all" => Ok(MergeRequestState::All),
+            _ => Err(eyre!(
+                "Unknown merge request state '{}', expected one of {}",
+                name,
+                MERGE_REQUEST_STATES.join(", ")
+            )),
+        }
+    }
+}
+
+impl MergeRequestState {
+    /// returns whether a merge request in the given state, e.g. 'merged', is in this state
+    pub fn includes(&self, state: &str) -> bool {
+        match self {
+            MergeRequestState::Opened => state == "opened",
+            MergeRequestState::Merged => state == "merged",
+            MergeRequestState::Closed => state == "closed",
+            MergeRequestState::All => true,
+        }
+    }
+
+    /// returns the state to request from REST APIs which only distinguish open and closed merge requests
+    fn rest_state(&self) -> &'static str {
+        match self {
+            MergeRequestState::Opened => "open",
+            MergeRequestState::Merged | MergeRequestState::Closed => "closed",
+            MergeRequestState::All => "all",
+        }
+    }
+}
+
+/**
+ * The criteria to list merge requests by. All given criteria must match.
+ */
+#[derive(Debug)]
+pub struct MergeRequestFilter {
+    pub state: MergeRequestState,
+    /// username of the author
+    pub author: Option<String>,
+    /// username of one of the assigned users
+    pub assignee: Option<String>,
+    /// username of one of the requested reviewers
+    pub reviewer: Option<String>,
+    /// labels which must all be set
+    pub labels: Vec<String>,
+    pub target_branch: Option<String>,
+    /// whether only drafts or only ready merge requests are listed
+    pub draft: Option<bool>,
+    /// the maximum number of merge requests to list
+    pub limit: usize,
+}
+
+impl MergeRequestFilter {
+    /// returns the state to request from REST APIs which only distinguish open and closed merge requests
+    fn rest_state(&self) -> &'static str {
+        self.state.rest_state()
+    }
+
+    /**
+     * Returns whether the given merge request matches this filter.
+     * Used by forges whose API cannot filter by these attributes, the state is expected to be filtered
+     * by the API already except for telling merged and closed merge requests apart.
+     */
+    fn matches(&self, mr: &MergeRequest) -> bool {
+        let state_matches = match self.state {
+            MergeRequestState::Merged => mr.state == "merged",
+            MergeRequestState::Closed => mr.state != "merged",
+            MergeRequestState::Opened | MergeRequestState::All => true,
+        };
+
+        state_matches
+            && self.author.as_ref().is_none_or(|user| user == &mr.author)
+            && self
+                .assignee
+                .as_ref()
+                .is_none_or(|user| mr.assignees.contains(user))
+            && self
+                .reviewer
+                .as_ref()
+                .is_none_or(|user| mr.reviewers.contains(user))
+            && self.labels.iter().all(|label| mr.labels.contains(label))
+            && self
+                .target_branch
+                .as_ref()
+                .is_none_or(|branch| branch == &mr.target_branch)
+            && self.draft.is_none_or(|draft| draft == mr.draft)
+    }
+}
+
+/**
+ * A merge request as listed by a forge, ordered by the time of its last update.
+ */
+#[derive(Debug, Serialize)]
+pub struct MergeRequestSummary {
+    pub iid: String,
+    pub title: String,
+    /// username of the author
+    pub author: String,
+    pub draft: bool,
+    /// status of the latest pipeline, e.g. 'success', if available
+    pub pipeline_status: Option<String>,
+    /// time of the last update in ISO 8601 format, e.g. '2021-08-19T12:00:00Z'
+    pub updated_at: String,
+    pub web_url: String,
+}
+
+/**
+ * An issue as returned by a forge.
+ */
+#[derive(Debug, Serialize)]
+pub struct Issue {
+    pub iid: String,
+    pub title: String,
+    pub description: String,
+    pub web_url: String,
+    /// username of the author
+    pub author: String,
+    /// either 'opened' or 'closed'
+    pub state: String,
+    pub labels: Vec<String>,
+    /// usernames of the assigned users
+    pub assignees: Vec<String>,
+    /// title of the milestone, if any
+    pub milestone: Option<String>,
+}
+
+/**
+ * The states issues can be filtered by.
+ */
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum IssueState {
+    Opened,
+    Closed,
+    All,
+}
+
+/// names of all issue states, as accepted by `IssueState::from_str`
+pub const ISSUE_STATES: &[&str] = &["opened", "closed", "all"];
+
+impl FromStr for IssueState {
+    type Err = Report;
+
+    fn from_str(name: &str) -> Result<Self, Self::Err> {
+        match name {
+            "opened" | "open" => Ok(IssueState::Opened),
+            "closed" => Ok(IssueState::Closed),
+            "all" => Ok(IssueState::All),
+            _ => Err(eyre!(
+                "Unknown issue state '{}', expected one of {}",
+                name,
+                ISSUE_STATES.join(", ")
+            )),
+        }
+    }
+}
+
+/**
+ * The criteria to list issues by. All given criteria must match.
+ */
+#[derive(Debug)]
+pub struct IssueFilter {
+    pub state: IssueState,
+    /// labels which must all be set
+    pub labels: Vec<String>,
+    /// username of one of the assigned users
+    pub assignee: Option<String>,
+    /// title of the milestone
+    pub milestone: Option<String>,
+    /// text to search for in the title and description
+    pub search: Option<String>,
+    /// whether only confidential or only public issues are listed
+    pub confidential: Option<bool>,
+    /// earliest due date in the format 'YYYY-MM-DD', inclusive
+    pub due_from: Option<String>,
+    /// latest due date in the format 'YYYY-MM-DD', inclusive
+    pub due_until: Option<String>,
+    /// the maximum number of issues to list
+    pub limit: usize,
+}
+
+impl IssueFilter {
+    /// returns the state to request from REST APIs
+    fn rest_state(&self) -> &'static str {
+        match self.state {
+            IssueState::Opened => "open",
+            IssueState::Closed => "closed",
+            IssueState::All => "all",
+        }
+    }
+
+    /// returns whether a due date filter is set
+    pub fn has_due_date_range(&self) -> bool {
+        self.due_from.is_some() || self.due_until.is_some()
+    }
+
+    /**
+     * Returns whether the given issue matches all criteria but the state and the search text,
+     * which are expected to be filtered by the API already.
+     * Used for the criteria the API of a forge cannot filter by.
+     */
+    fn matches(&self, issue: &IssueSummary) -> bool {
+        // due dates may include a time, which is ignored:
+        let due_date = issue
+            .due_date
+            .as_ref()
+            .map(|date| &date[..date.len().min(10)]);
+        let due_date_matches = !self.has_due_date_range()
+            || due_date.is_some_and(|date| {
+                self.due_from
+                    .as_ref()
+                    .is_none_or(|from| date >= from.as_str())
+                    && self
+                        .due_until
+                        .as_ref()
+                        .is_none_or(|until| date <= until.as_str())
+            });
+
+        self.labels.iter().all(|label| issue.labels.contains(label))
+            && self
+                .assignee
+                .as_ref()
+                .is_none_or(|user| issue.assignees.contains(user))
+            && self
+                .milestone
+                .as_ref()
+                .is_none_or(|title| issue.milestone.as_ref() == Some(title))
+            && self
+                .confidential
+                .is_none_or(|confidential| confidential == issue.confidential)
+            && due_date_matches
+    }
+}
+
+/**
+ * An issue as listed by a forge.
+ */
+#[derive(Debug, Serialize)]
+pub struct IssueSummary {
+    pub iid: String,
+    pub title: String,
+    /// either 'opened' or 'closed'
+    pub state: String,
+    /// username of the author
+    pub author: String,
+    /// usernames of the assigned users
+    pub assignees: Vec<String>,
+    pub labels: Vec<String>,
+    /// title of the milestone, if any
+    pub milestone: Option<String>,
+    /// due date in ISO 8601 format, possibly including a time
+    pub due_date: Option<String>,
+    pub confidential: bool,
+    /// time of the last update in ISO 8601 format, e.g. '2021-08-19T12:00:00Z'
+    pub updated_at: String,
+    pub web_url: String,
+}
+
+/**
+ * A comment on an issue or merge request.
+ */
+#[derive(Debug, Serialize)]
+pub struct Note {
+    /// username of the author
+    pub author: String,
+    /// the markdown text of the comment
+    pub body: String,
+    /// time of creation in ISO 8601 format, e.g. '2021-08-19T12:00:00Z'
+    pub created_at: String,
+    /// file and line the comment refers to if it is a comment on the diff, e.g. 'src/main.rs:12'
+    pub position: Option<String>,
+}
+
+/**
+ * A thread of comments on an issue or merge request, ordered from oldest to newest.
+ */
+#[derive(Debug, Serialize)]
+pub struct Discussion {
+    pub notes: Vec<Note>,
+    /// whether the thread is resolved, `None` if it cannot be resolved
+    pub resolved: Option<bool>,
+}
+
+/**
+ * An issue which has just been created on a forge.
+ */
+#[derive(Debug, Serialize)]
+pub struct CreatedIssue {
+    pub iid: String,
+    pub web_url: String,
+}
+
+/**
+ * A merge request which has just been created on a forge.
+ */
+#[derive(Debug, Serialize)]
+pub struct CreatedMergeRequest {
+    pub iid: String,
+    pub web_url: String,
+}
+
+/**
+ * Abstraction over the API of a hosting backend (Gitlab, Github, ...).
+ * An instance is always bound to a single project on a single host.
+ */
+#[async_trait]
+pub trait Forge {
+    /**
+     * Fetches the merge requests of the given source branch, most recent first. The state is only a hint:
+     * backends which cannot filter by branch may omit merge requests in other states, or merged and closed
+     * ones if no state is given and the branch has an open merge request.
+     */
+    async fn get_merge_requests(
+        &self,
+        branch_name: &str,
+        state: Option<MergeRequestState>,
+    ) -> Result<Vec<MergeRequest>, Report>;
+
+    /// fetches the merge request with the given iid
+    async fn get_merge_request_by_iid(&self, iid: &str) -> Result<MergeRequest, Report>;
+
+    /// fetches the comment threads of the merge request with the given iid, oldest first
+    async fn get_merge_request_discussions(&self, iid: &str) -> Result<Vec<Discussion>, Report>;
+
+    /// lists the merge requests matching the given filter, most recently updated first
+    async fn list_merge_requests(
+        &self,
+        filter: &MergeRequestFilter,
+    ) -> Result<Vec<MergeRequestSummary>, Report>;
+
+    /// applies the given changes to the given merge request
+    async fn update_merge_request(
+        &self,
+        mr: &MergeRequest,
+        update: &MergeRequestUpdate,
+    ) -> Result<(), Report>;
+
+    /// fetches the name of the project's default branch, e.g. 'main'
+    async fn get_default_branch(&self) -> Result<String, Report>;
+
+    /// creates a new merge request from the given source branch into the given target branch
+    async fn create_merge_request(
+        &self,
+        source_branch: &str,
+        target_branch: &str,
+        title: &str,
+        description: &Option<String>,
+    ) -> Result<CreatedMergeRequest, Report>;
+
+    /// creates a new issue with the given title, optional description and labels
+    async fn create_issue(
+        &self,
+        title: &str,
+        description: &Option<String>,
+        labels: &[String],
+    ) -> Result<CreatedIssue, Report>;
+
+    /// lists the issues matching the given filter, most recently updated first if the forge supports sorting them
+    async fn list_issues(&self, filter: &IssueFilter) -> Result<Vec<IssueSummary>, Report>;
+
+    /// fetches the issue with the given iid
+    async fn get_issue(&self, iid: &str) -> Result<Issue, Report>;
+
+    /// fetches the comment threads of the issue with the given iid, oldest first
+    async fn get_issue_discussions(&self, iid: &str) -> Result<Vec<Discussion>, Report>;
+
+    /// updates the title and/or description of the issue with the given iid, `None` values are left unchanged
+    async fn update_issue(
+        &self,
+        iid: &str,
+        title: &Option<String>,
+        description: &Option<String>,
+    ) -> Result<(), Report>;
+}
+
+/**
+ * The kinds of forges supported by this CLI.
+ */
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ForgeKind {
+    Gitlab,
+    Github,
+    Gitea,
+}
+
+/// the names of all forge kinds, as accepted by `ForgeKind::from_str`
+pub const FORGE_KINDS: &[&str] = &["gitlab", "github", "gitea", "forgejo"];
+
+impl ForgeKind {
+    /// returns the name of this forge kind, as accepted by `ForgeKind::from_str`
+    pub fn name(&self) -> &'static str {
+        match self {
+            ForgeKind::Gitlab => "gitlab",
+            ForgeKind::Github => "github",
+            ForgeKind::Gitea => "gitea",
+        }
+    }
+
+    /// returns the host used if none is configured explicitly
+    pub fn default_host(&self) -> &'static str {
+        match self {
+            ForgeKind::Gitlab => "https://gitlab.com",
+            ForgeKind::Github => "https://github.com",
+            ForgeKind::Gitea => "https://codeberg.org",
+        }
+    }
+}
+
+impl FromStr for ForgeKind {
+    type Err = AppError;
+
+    fn from_str(name: &str) -> Result<Self, Self::Err> {
+        match name.to_lowercase().as_str() {
+            "gitlab" => Ok(ForgeKind::Gitlab),
+            "github" => Ok(ForgeKind::Github),
+            "gitea" | "forgejo" => Ok(ForgeKind::Gitea),
+            _ => Err(AppError::UnknownForge(name.to_string())),
+        }
+    }
+}
+
+/// splits the given project path into owner and repository name, e.g. 'myusername/myproject'
+fn split_project_path<'a>(path: &'a str, forge_name: &str) -> Result<(&'a str, &'a str), Report> {
+    path.split_once('/').ok_or_else(|| {
+        eyre!(
+            "Invalid {} project path '{}', expected '<owner>/<repository>'",
+            forge_name,
+            path
+        )
+    })
+}
+
+/**
+ * Returns the forge backend selected by the given global arguments.
+ * If an upstream project is configured, the backend works on the upstream project and
+ * opens merge requests from the configured project, i.e. the fork.
+ */
+pub fn get_forge(global_args: &GlobalArgs) -> Result<Box<dyn Forge + Send + Sync>, Report> {
+    let (project_path, fork_path) = match &global_args.upstream {
+        Some(upstream) => (upstream.as_str(), Some(global_args.project_path.as_str())),
+        None => (global_args.project_path.as_str(), None),
+    };
+
+    match global_args.forge {
+        ForgeKind::Gitlab => Ok(Box::new(gitlab::Gitlab::new(
+            &global_args.host,
+            &global_args.token,
+            project_path,
+            fork_path,
+        ))),
+        ForgeKind::Github => Ok(Box::new(github::Github::new(
+            &global_args.host,
+            &global_args.token,
+            project_path,
+            fork_path,
+        )?)),
+        ForgeKind::Gitea => Ok(Box::new(gitea::Gitea::new(
+            &global_args.host,
+            &global_args.token,
+            project_path,
+            fork_path,
+        )?)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn mr_filter(state: MergeRequestState) -> MergeRequestFilter {
+        MergeRequestFilter {
+            state,
+            author: None,
+            assignee: None,
+            reviewer: None,
+            labels: vec![],
+            target_branch: None,
+            draft: None,
+            limit: 20,
+        }
+    }
+
+    fn merge_request(state: &str) -> MergeRequest {
+        MergeRequest {
+            iid: String::from("1"),
+            title: String::from("Fix the parser"),
+            description: String::new(),
+            web_url: String::from("https://gitlab.com/g/p/-/merge_requests/1"),
+            author: String::from("alice"),
+            state: state.to_string(),
+            source_branch: String::from("fix"),
+            target_branch: String::from("main"),
+            draft: false,
+            labels: vec![String::from("bug"), String::from("backend")],
+            assignees: vec![String::from("bob")],
+            reviewers: vec![String::from("carol")],
+            milestone: None,
+        }
+    }
+
+    #[test]
+    fn matches_merge_requests_without_criteria() {
+        assert!(mr_filter(MergeRequestState::All).matches(&merge_request("merged")));
+        assert!(mr_filter(MergeRequestState::Opened).matches(&merge_request("opened")));
+    }
+
+    #[test]
+    fn tells_merged_and_closed_merge_requests_apart() {
+        let merged = merge_request("merged");
+        let closed = merge_request("closed");
+        assert!(mr_filter(MergeRequestState::Merged).matches(&merged));
+        assert!(!mr_filter(MergeRequestState::Merged).matches(&closed));
+        assert!(mr_filter(MergeRequestState::Closed).matches(&closed));
+        assert!(!mr_filter(MergeRequestState::Closed).matches(&merged));
+    }
+
+    #[test]
+    fn matches_merge_requests_by_all_criteria() {
+        let mr = merge_request("opened");
+        let filter = MergeRequestFilter {
+            author: Some(String::from("alice")),
+            assignee: Some(String::from("bob")),
+            reviewer: Some(String::from("carol")),
+            labels: vec![String::from("backend"), String::from("bug")],
+            target_branch: Some(String::from("main")),
+            draft: Some(false),
+            ..mr_filter(MergeRequestState::Opened)
+        };
+        assert!(filter.matches(&mr));
+
+        let mismatches = [
+            MergeRequestFilter {
+                author: Some(String::from("bob")),
+                ..mr_filter(MergeRequestState::Opened)
+            },
+            MergeRequestFilter {
+                assignee: Some(String::from("alice")),
+                ..mr_filter(MergeRequestState::Opened)
+            },
+            MergeRequestFilter {
+                reviewer: Some(String::from("bob")),
+                ..mr_filter(MergeRequestState::Opened)
+            },
+            MergeRequestFilter {
+                labels: vec![String::from("bug"), String::from("frontend")],
+                ..mr_filter(MergeRequestState::Opened)
+            },
+            MergeRequestFilter {
+                target_branch: Some(String::from("develop")),
+                ..mr_filter(MergeRequestState::Opened)
+            },
+            MergeRequestFilter {
+                draft: Some(true),
+                ..mr_filter(MergeRequestState::Opened)
+            },
+        ];
+        for filter in &mismatches {
+            assert!(!filter.matches(&mr), "{:?} should not match", filter);
+        }
+    }
+
+    #[test]
+    fn parses_merge_request_states() {
+        assert_eq!(
+            "open".parse::<MergeRequestState>().unwrap(),
+            MergeRequestState::Opened
+        );
+        assert_eq!(
+            "merged".parse::<MergeRequestState>().unwrap(),
+            MergeRequestState::Merged
+        );
+        assert!("draft".parse::<MergeRequestState>().is_err());
+        assert!(MergeRequestState::Closed.includes("closed"));
+        assert!(!MergeRequestState::Closed.includes("merged"));
+        assert!(MergeRequestState::All.includes("merged"));
+    }
+
+    fn issue_filter() -> IssueFilter {
+        IssueFilter {
+            state: IssueState::Opened,
+            labels: vec![],
+            assignee: None,
+            milestone: None,
+            search: None,
+            confidential: None,
+            due_from: None,
+            due_until: None,
+            limit: 20,
+        }
+    }
+
+    fn issue(due_date: Option<&str>) -> IssueSummary {
+        IssueSummary {
+            iid: String::from("7"),
+            title: String::from("Crash on start"),
+            state: String::from("opened"),
+            author: String::from("alice"),
+            assignees: vec![String::from("bob")],
+            labels: vec![String::from("bug")],
+            milestone: Some(String::from("v1")),
+            due_date: due_date.map(String::from),
+            confidential: false,
+            updated_at: String::from("2021-08-19T12:00:00Z"),
+            web_url: String::from("https://gitlab.com/g/p/-/issues/7"),
+        }
+    }
+
+    #[test]
+    fn matches_issues_by_all_criteria() {
+        let issue = issue(None);
+        assert!(issue_filter().matches(&issue));
+        assert!(IssueFilter {
+            labels: vec![String::from("bug")],
+            assignee: Some(String::from("bob")),
+            milestone: Some(String::from("v1")),
+            confidential: Some(false),
+            ..issue_filter()
+        }
+        .matches(&issue));
+
+        let mismatches = [
+            IssueFilter {
+                labels: vec![String::from("bug"), String::from("ui")],
+                ..issue_filter()
+            },
+            IssueFilter {
+                assignee: Some(String::from("alice")),
+                ..issue_filter()
+            },
+            IssueFilter {
+                milestone: Some(String::from("v2")),
+                ..issue_filter()
+            },
+            IssueFilter {
+                confidential: Some(true),
+                ..issue_filter()
+            },
+        ];
+        for filter in &mismatches {
+            assert!(!filter.matches(&issue), "{:?} should not match", filter);
+        }
+    }
+
+    #[test]
+    fn matches_issues_by_inclusive_due_date_range() {
+        let filter = IssueFilter {
+            due_from: Some(String::from("2021-08-01")),
+            due_until: Some(String::from("2021-08-31")),
+            ..issue_filter()
+        };
+        assert!(filter.matches(&issue(Some("2021-08-01"))));
+        assert!(filter.matches(&issue(Some("2021-08-31T23:59:59Z"))));
+        assert!(!filter.matches(&issue(Some("2021-09-01"))));
+        assert!(!filter.matches(&issue(Some("2021-07-31"))));
+        assert!(!filter.matches(&issue(None)));
+
+        let open_ended = IssueFilter {
+            due_from: Some(String::from("2021-08-01")),
+            ..issue_filter()
+        };
+        assert!(open_ended.matches(&issue(Some("2030-01-01"))));
+        assert!(issue_filter().matches(&issue(Some("2000-01-01"))));
+    }
+
+    #[test]
+    fn parses_issue_states() {
+        assert_eq!("open".parse::<IssueState>().unwrap(), IssueState::Opened);
+        assert_eq!("all".parse::<IssueState>().unwrap(), IssueState::All);
+        assert!("merged".parse::<IssueState>().is_err());
+    }
+}
@@ -0,0 +1,66 @@
+use eyre::{Context, Report};
+use log::debug;
+use reqwest::RequestBuilder;
+use serde::de::DeserializeOwned;
+
+use crate::errors::AppError;
+
+/**
+ * Sends the given request to a REST API and parses the JSON response body.
+ * Non-successful status codes are reported as `AppError::RestError` using the given error message.
+ */
+pub async fn send_json<T: DeserializeOwned>(
+    request: RequestBuilder,
+    error_message: &str,
+) -> Result<T, Report> {
+    let res = send(request, error_message).await?;
+
+    res.json()
+        .await
+        .wrap_err("Failed to parse the response from the API")
+}
+
+/**
+ * Fetches all pages of a paginated list from a REST API. `request` returns the request of the page with
+ * the given number, starting at 1, which is expected to contain at most `page_size` items.
+ */
+pub async fn send_json_pages<T: DeserializeOwned>(
+    request: impl Fn(usize) -> RequestBuilder,
+    page_size: usize,
+    error_message: &str,
+) -> Result<Vec<T>, Report> {
+    let mut items = vec![];
+    for page in 1.. {
+        let page_items: Vec<T> = send_json(request(page), error_message).await?;
+        let last_page = page_items.len() < page_size;
+        items.extend(page_items);
+        if last_page {
+            break;
+        }
+    }
+
+    Ok(items)
+}
+
+/**
+ * Sends the given request to a REST API, ignoring the response body on success.
+ */
+pub async fn send(
+    request: RequestBuilder,
+    error_message: &str,
+) -> Result<reqwest::Response, Report> {
+    let res = request.send().await.wrap_err("API request failed")?;
+    debug!("response '{}' for {}", res.status(), res.url());
+
+    let status = res.status();
+    if status.is_success() {
+        Ok(res)
+    } else {
+        let details = res.text().await.unwrap_or_default();
+        Err(AppError::RestError {
+            message: String::from(error_message),
+            status: status.as_u16(),
+            details,
+        })?
+    }
+}
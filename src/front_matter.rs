@@ -0,0 +1,329 @@
+use eyre::{eyre, Context, Report};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    errors::AppError,
+    forges::{MergeRequest, MergeRequestUpdate},
+};
+
+/// line delimiting the TOML front matter in markdown buffers
+const TOML_DELIMITER: &str = "+++";
+/// first line of the property drawer in ORG buffers
+const ORG_DRAWER_START: &str = ":PROPERTIES:";
+/// last line of the property drawer in ORG buffers
+const ORG_DRAWER_END: &str = ":END:";
+
+/**
+ * The merge request attributes which are edited in the header of the editor buffer.
+ * Attributes missing in the header are left unchanged.
+ */
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct MergeRequestFields {
+    pub title: Option<String>,
+    pub draft: Option<bool>,
+    pub labels: Option<Vec<String>>,
+    pub assignees: Option<Vec<String>>,
+    pub reviewers: Option<Vec<String>>,
+    /// title of the milestone, an empty title removes the milestone
+    pub milestone: Option<String>,
+}
+
+impl MergeRequestFields {
+    /// returns the current attributes of the given merge request
+    pub fn from_mr(mr: &MergeRequest) -> MergeRequestFields {
+        MergeRequestFields {
+            title: Some(mr.title.clone()),
+            draft: Some(mr.draft),
+            labels: Some(mr.labels.clone()),
+            assignees: Some(mr.assignees.clone()),
+            reviewers: Some(mr.reviewers.clone()),
+            milestone: Some(mr.milestone.clone().unwrap_or_default()),
+        }
+    }
+
+    /**
+     * Returns the changes required to get from the `original` attributes to these attributes.
+     * The description is left unchanged.
+     */
+    pub fn to_update(&self, original: &MergeRequestFields) -> Result<MergeRequestUpdate, Report> {
+        if let Some(title) = &self.title {
+            if title.trim().is_empty() {
+                return Err(AppError::Aborted(String::from("the title is empty")).into());
+            }
+        }
+
+        let changed_list = |new: &Option<Vec<String>>, old: &Option<Vec<String>>| {
+            new.as_ref()
+                .filter(|new| !same_elements(new, old.as_deref().unwrap_or_default()))
+                .cloned()
+        };
+        let trimmed = |value: &Option<String>| value.as_ref().map(|value| value.trim().to_string());
+
+        Ok(MergeRequestUpdate {
+            title: trimmed(&self.title).filter(|title| Some(title) != original.title.as_ref()),
+            description: None,
+            draft: self.draft.filter(|draft| Some(*draft) != original.draft),
+            labels: changed_list(&self.labels, &original.labels),
+            assignees: changed_list(&self.assignees, &original.assignees),
+            reviewers: changed_list(&self.reviewers, &original.reviewers),
+            milestone: trimmed(&self.milestone)
+                .filter(|milestone| Some(milestone) != trimmed(&original.milestone).as_ref())
+                .map(|milestone| Some(milestone).filter(|milestone| !milestone.is_empty())),
+        })
+    }
+
+    /**
+     * Returns the given body prefixed with these attributes, either as TOML front matter
+     * or, in ORG mode, as property drawer.
+     */
+    pub fn render(&self, body: &str, org_mode: bool) -> Result<String, Report> {
+        let header = if org_mode {
+            self.render_org_drawer()
+        } else {
+            let toml = toml::to_string(self).wrap_err("Could not serialize the front matter")?;
+            format!("{}\n{}{}\n", TOML_DELIMITER, toml, TOML_DELIMITER)
+        };
+
+        Ok(format!("{}\n{}", header, body))
+    }
+
+    /**
+     * Splits the given buffer into the attributes in its header and the remaining body.
+     * Returns `None` as attributes if the buffer has no header.
+     */
+    pub fn parse(
+        text: &str,
+        org_mode: bool,
+    ) -> Result<(Option<MergeRequestFields>, String), Report> {
+        let (start, end) = if org_mode {
+            (ORG_DRAWER_START, ORG_DRAWER_END)
+        } else {
+            (TOML_DELIMITER, TOML_DELIMITER)
+        };
+        let (header, body) = match split_header(text, start, end) {
+            Some(parts) => parts,
+            None => return Ok((None, text.to_string())),
+        };
+
+        let fields = if org_mode {
+            Self::parse_org_drawer(header)?
+        } else {
+            toml::from_str(header).wrap_err("Invalid front matter")?
+        };
+        Ok((Some(fields), body.trim_start_matches('\n').to_string()))
+    }
+
+    fn render_org_drawer(&self) -> String {
+        let mut lines = vec![ORG_DRAWER_START.to_string()];
+        let mut add = |key: &str, value: Option<String>| {
+            if let Some(value) = value {
+                lines.push(format!(":{}: {}", key, value).trim_end().to_string());
+            }
+        };
+        add("TITLE", self.title.clone());
+        add("DRAFT", self.draft.map(|draft| draft.to_string()));
+        add("LABELS", self.labels.as_ref().map(|list| list.join(", ")));
+        add(
+            "ASSIGNEES",
+            self.assignees.as_ref().map(|list| list.join(", ")),
+        );
+        add(
+            "REVIEWERS",
+            self.reviewers.as_ref().map(|list| list.join(", ")),
+        );
+        add("MILESTONE", self.milestone.clone());
+        lines.push(ORG_DRAWER_END.to_string());
+
+        format!("{}\n", lines.join("\n"))
+    }
+
+    fn parse_org_drawer(drawer: &str) -> Result<MergeRequestFields, Report> {
+        let mut fields = MergeRequestFields::default();
+
+        for line in drawer.lines().filter(|line| !line.trim().is_empty()) {
+            let (key, value) = line
+                .trim()
+                .strip_prefix(':')
+                .and_then(|line| line.split_once(':'))
+                .ok_or_else(|| eyre!("Invalid property '{}', expected ':KEY: value'", line))?;
+            let value = value.trim();
+            let list = || {
+                value
+                    .split(',')
+                    .map(str::trim)
+                    .filter(|item| !item.is_empty())
+                    .map(String::from)
+                    .collect()
+            };
+
+            match key.to_uppercase().as_str() {
+                "TITLE" => fields.title = Some(value.to_string()),
+                "DRAFT" => fields.draft = Some(parse_org_bool(value)?),
+                "LABELS" => fields.labels = Some(list()),
+                "ASSIGNEES" => fields.assignees = Some(list()),
+                "REVIEWERS" => fields.reviewers = Some(list()),
+                "MILESTONE" => fields.milestone = Some(value.to_string()),
+                _ => return Err(eyre!("Unknown property '{}'", key)),
+            }
+        }
+
+        Ok(fields)
+    }
+}
+
+/**
+ * Splits the given text into the lines between the start and end delimiter lines and the text following
+ * them. Returns `None` if the text does not start with the start delimiter.
+ */
+fn split_header<'a>(text: &'a str, start: &str, end: &str) -> Option<(&'a str, &'a str)> {
+    let rest = text.strip_prefix(start)?;
+    let rest = rest
+        .strip_prefix("\r\n")
+        .or_else(|| rest.strip_prefix('\n'))?;
+
+    let mut offset = 0;
+    for line in rest.split_inclusive('\n') {
+        if line.trim_end() == end {
+            return Some((&rest[..offset], &rest[offset + line.len()..]));
+        }
+        offset += line.len();
+    }
+    None
+}
+
+/// parses the value of a boolean ORG property, e.g. 'true' or 't'
+fn parse_org_bool(value: &str) -> Result<bool, Report> {
+    match value.to_lowercase().as_str() {
+        "true" | "t" | "yes" => Ok(true),
+        "false" | "nil" | "no" | "" => Ok(false),
+        _ => Err(eyre!("Invalid boolean value '{}'", value)),
+    }
+}
+
+/// returns whether both lists contain the same elements, regardless of their order
+fn same_elements(a: &[String], b: &[String]) -> bool {
+    let mut a = a.to_vec();
+    let mut b = b.to_vec();
+    a.sort();
+    b.sort();
+    a == b
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fields() -> MergeRequestFields {
+        MergeRequestFields {
+            title: Some(String::from("Fix the parser")),
+            draft: Some(true),
+            labels: Some(vec![String::from("bug"), String::from("backend")]),
+            assignees: Some(vec![String::from("alice")]),
+            reviewers: Some(vec![]),
+            milestone: Some(String::new()),
+        }
+    }
+
+    #[test]
+    fn round_trips_toml_front_matter() {
+        let buffer = fields().render("Description\n", false).unwrap();
+        assert!(buffer.starts_with("+++\ntitle = \"Fix the parser\"\n"));
+        assert_eq!(
+            MergeRequestFields::parse(&buffer, false).unwrap(),
+            (Some(fields()), String::from("Description\n"))
+        );
+    }
+
+    #[test]
+    fn round_trips_org_drawer() {
+        let buffer = fields().render("Description\n", true).unwrap();
+        assert_eq!(
+            buffer,
+            ":PROPERTIES:\n:TITLE: Fix the parser\n:DRAFT: true\n:LABELS: bug, backend\n\
+             :ASSIGNEES: alice\n:REVIEWERS:\n:MILESTONE:\n:END:\n\nDescription\n"
+        );
+        assert_eq!(
+            MergeRequestFields::parse(&buffer, true).unwrap(),
+            (Some(fields()), String::from("Description\n"))
+        );
+    }
+
+    #[test]
+    fn parses_buffers_without_header() {
+        assert_eq!(
+            MergeRequestFields::parse("Just text\n", false).unwrap(),
+            (None, String::from("Just text\n"))
+        );
+        assert_eq!(
+            MergeRequestFields::parse("+++\ntitle = \"unterminated\"\n", false).unwrap(),
+            (None, String::from("+++\ntitle = \"unterminated\"\n"))
+        );
+    }
+
+    #[test]
+    fn parses_partial_org_drawer() {
+        let (parsed, body) = MergeRequestFields::parse(
+            ":PROPERTIES:\n:draft: nil\n:Labels: a,, b \n:END:\nText",
+            true,
+        )
+        .unwrap();
+        assert_eq!(
+            parsed,
+            Some(MergeRequestFields {
+                draft: Some(false),
+                labels: Some(vec![String::from("a"), String::from("b")]),
+                ..MergeRequestFields::default()
+            })
+        );
+        assert_eq!(body, "Text");
+    }
+
+    #[test]
+    fn rejects_invalid_headers() {
+        assert!(MergeRequestFields::parse("+++\ncolour = \"red\"\n+++\n", false).is_err());
+        assert!(MergeRequestFields::parse(":PROPERTIES:\n:COLOUR: red\n:END:\n", true).is_err());
+        assert!(MergeRequestFields::parse(":PROPERTIES:\n:DRAFT: maybe\n:END:\n", true).is_err());
+        assert!(MergeRequestFields::parse(":PROPERTIES:\nno property\n:END:\n", true).is_err());
+    }
+
+    #[test]
+    fn updates_only_changed_fields() {
+        let edited = MergeRequestFields {
+            title: Some(String::from(" Fix the parser ")),
+            draft: Some(false),
+            labels: Some(vec![String::from("backend"), String::from("bug")]),
+            assignees: Some(vec![String::from("bob")]),
+            reviewers: None,
+            milestone: Some(String::from("v1")),
+        };
+        let update = edited.to_update(&fields()).unwrap();
+
+        assert_eq!(update.title, None);
+        assert_eq!(update.description, None);
+        assert_eq!(update.draft, Some(false));
+        assert_eq!(update.labels, None);
+        assert_eq!(update.assignees, Some(vec![String::from("bob")]));
+        assert_eq!(update.reviewers, None);
+        assert_eq!(update.milestone, Some(Some(String::from("v1"))));
+    }
+
+    #[test]
+    fn removes_emptied_milestone() {
+        let original = MergeRequestFields {
+            milestone: Some(String::from("v1")),
+            ..fields()
+        };
+        let update = fields().to_update(&original).unwrap();
+        assert_eq!(update.milestone, Some(None));
+    }
+
+    #[test]
+    fn rejects_empty_title() {
+        let edited = MergeRequestFields {
+            title: Some(String::from("  ")),
+            ..fields()
+        };
+        assert!(edited.to_update(&fields()).is_err());
+    }
+}
@@ -1,44 +1,179 @@
 use clap::ArgMatches;
-use cli::{build_cli, ARG_BRANCH};
+use cli::{build_cli, ARG_BRANCH, ARG_IID, ARG_STATE};
 use cmd_lib::run_fun;
 use commands::CommandResult;
 use eyre::Context;
-use eyre::Report;
-use loggerv;
-use tokio;
+use eyre::{eyre, Report};
+use forges::{Forge, MergeRequest, MergeRequestState};
+use output::{print_error, OutputFormat};
 
 pub mod cli;
 pub mod commands;
+pub mod config;
+pub mod convert;
+pub mod drafts;
+pub mod editor;
 pub mod errors;
+pub mod forges;
+pub mod front_matter;
+pub mod output;
+pub mod prompt;
 pub mod queries;
+pub mod remote;
+pub mod render;
+pub mod settings;
+pub mod table;
+pub mod templates;
+pub mod token;
 
 /**
  * Main entry point of the app. Parses the command line arguments
  * and passes them to `run_command`, which then will run the sub command
  * based on the given CLI arguments.
+ *
+ * With `--output json`, errors are printed as JSON document to stdout and the process exits with status 1.
  */
 #[tokio::main]
 pub async fn run() -> CommandResult {
     let cli_args = build_cli().get_matches();
+    let output = OutputFormat::from_cli_args(&cli_args);
 
-    loggerv::Logger::new()
+    let mut logger = loggerv::Logger::new()
         .verbosity(cli_args.occurrences_of("v"))
         .level(true)
-        .module_path(false)
-        .init()
-        .unwrap();
+        .module_path(false);
+    // stdout is reserved for the JSON document:
+    if output == OutputFormat::Json {
+        for level in &[log::Level::Info, log::Level::Debug, log::Level::Trace] {
+            logger = logger.output(level, loggerv::Output::Stderr);
+        }
+    }
+    logger.init().unwrap();
 
-    commands::run_command(&cli_args).await
+    let result = commands::run_command(&cli_args).await;
+    match (result, output) {
+        (Err(err), OutputFormat::Json) => {
+            print_error(&err)?;
+            std::process::exit(1);
+        }
+        (result, _) => result,
+    }
 }
 
 /**
  * Returns the name of the current branch, either provided by the given CLI arguments,
  * or falling back to the name of the currently checked out branch.
  */
-pub fn get_branch_name<'a>(cli_args: &ArgMatches<'a>) -> Result<String, Report> {
+pub fn get_branch_name(cli_args: &ArgMatches) -> Result<String, Report> {
     if let Some(branch) = cli_args.value_of(ARG_BRANCH) {
         Ok(branch.to_string())
     } else {
         run_fun!(git branch --show-current).wrap_err("failed to run git to fetch current branch")
     }
 }
+
+/**
+ * Returns the merge request chosen by the given CLI arguments: the one with the iid passed via --iid,
+ * or the one of the branch returned by `get_branch_name` as chosen by `select_merge_request`.
+ */
+pub async fn get_merge_request(
+    cli_args: &ArgMatches<'_>,
+    forge: &dyn Forge,
+) -> Result<MergeRequest, Report> {
+    if let Some(iid) = cli_args.value_of(ARG_IID) {
+        return forge
+            .get_merge_request_by_iid(iid)
+            .await
+            .wrap_err_with(|| format!("Failed to fetch merge request {}", iid));
+    }
+
+    let branch_name = get_branch_name(cli_args)?;
+    let state = cli_args
+        .value_of(ARG_STATE)
+        .map(str::parse::<MergeRequestState>)
+        .transpose()?;
+    select_merge_request(forge, &branch_name, state).await
+}
+
+/**
+ * Returns the merge request of the given source branch in the given state.
+ * Without a state, open merge requests are preferred over merged and closed ones, which are only
+ * considered if the branch has no open merge request. If several merge requests remain,
+ * the user is asked to choose one.
+ */
+pub async fn select_merge_request(
+    forge: &dyn Forge,
+    branch_name: &str,
+    state: Option<MergeRequestState>,
+) -> Result<MergeRequest, Report> {
+    let mrs = forge
+        .get_merge_requests(branch_name, state)
+        .await
+        .wrap_err_with(|| {
+            format!(
+                "Failed to fetch the merge requests of branch {}",
+                branch_name
+            )
+        })?;
+
+    let mut candidates: Vec<MergeRequest> = match state {
+        Some(state) => mrs
+            .into_iter()
+            .filter(|mr| state.includes(&mr.state))
+            .collect(),
+        None if mrs.iter().any(|mr| mr.state == "opened") => {
+            mrs.into_iter().filter(|mr| mr.state == "opened").collect()
+        }
+        None => mrs,
+    };
+
+    match candidates.len() {
+        0 => Err(eyre!(
+            "No {}merge request found for branch {}",
+            state
+                .filter(|state| *state != MergeRequestState::All)
+                .map(|state| format!("{:?} ", state).to_lowercase())
+                .unwrap_or_default(),
+            branch_name
+        )),
+        1 => Ok(candidates.remove(0)),
+        count => {
+            let options: Vec<String> = candidates
+                .iter()
+                .map(|mr| {
+                    format!(
+                        "{}: {} ({}, into {})",
+                        mr.iid, mr.title, mr.state, mr.target_branch
+                    )
+                })
+                .collect();
+            let question = format!(
+                "Branch {} has {} merge requests, which one do you mean?",
+                branch_name, count
+            );
+            let index = prompt::choose(&question, &options).wrap_err_with(|| {
+                format!(
+                    "Several merge requests found for branch {}: {}. Use --iid or --state to choose one",
+                    branch_name,
+                    options.join("; ")
+                )
+            })?;
+            Ok(candidates.remove(index))
+        }
+    }
+}
+
+/**
+ * Returns the messages of all commits in the given revision range, e.g. 'origin/main..my-branch',
+ * ordered from oldest to newest.
+ */
+pub fn get_commit_messages(revision_range: &str) -> Result<Vec<String>, Report> {
+    let log = run_fun!(git log --reverse "--format=%B%x00" $revision_range)
+        .wrap_err("failed to run git to fetch the commit messages")?;
+
+    Ok(log
+        .split('\0')
+        .map(|message| message.trim().to_string())
+        .filter(|message| !message.is_empty())
+        .collect())
+}
@@ -0,0 +1,63 @@
+use clap::ArgMatches;
+use eyre::{Context, Report};
+use serde::Serialize;
+use serde_json::json;
+
+use crate::{cli::ARG_OUTPUT, errors::AppError};
+
+/// the names of all output formats, as accepted by `--output`
+pub const OUTPUT_FORMATS: &[&str] = &["text", "json"];
+
+/// the kind reported for errors which are not described by an `AppError`
+const OTHER_ERROR_KIND: &str = "other";
+
+/**
+ * The formats commands can print their results in.
+ */
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum OutputFormat {
+    /// human readable text, e.g. tables and rendered markdown
+    Text,
+    /// a single JSON document per command, meant to be read by scripts
+    Json,
+}
+
+impl OutputFormat {
+    /// returns the output format chosen by the given top level CLI arguments
+    pub fn from_cli_args(arg_matches: &ArgMatches) -> OutputFormat {
+        match arg_matches.value_of(ARG_OUTPUT) {
+            Some("json") => OutputFormat::Json,
+            _ => OutputFormat::Text,
+        }
+    }
+}
+
+/// prints the given value as pretty-printed JSON document to stdout
+pub fn print_json(value: &impl Serialize) -> Result<(), Report> {
+    let json = serde_json::to_string_pretty(value).wrap_err("Failed to serialize the output")?;
+    println!("{}", json);
+    Ok(())
+}
+
+/**
+ * Prints the given error as JSON document to stdout, e.g.
+ * `{"error": {"kind": "rest_error", "message": "...", "causes": ["..."], "status": 404}}`.
+ * The kind is derived from the first `AppError` in the chain of causes, falling back to 'other'.
+ */
+pub fn print_error(err: &Report) -> Result<(), Report> {
+    let app_error = err
+        .chain()
+        .find_map(|cause| cause.downcast_ref::<AppError>());
+    let causes: Vec<String> = err.chain().skip(1).map(ToString::to_string).collect();
+
+    let mut error = json!({
+        "kind": app_error.map_or(OTHER_ERROR_KIND, AppError::kind),
+        "message": err.to_string(),
+        "causes": causes,
+    });
+    if let Some(AppError::RestError { status, .. }) = app_error {
+        error["status"] = json!(status);
+    }
+
+    print_json(&json!({ "error": error }))
+}
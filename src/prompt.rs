@@ -0,0 +1,40 @@
+use eyre::{eyre, Context, Report};
+use std::io::{self, BufRead, IsTerminal, Write};
+
+/**
+ * Asks the user to choose one of the given options by entering its number.
+ * Returns the index of the chosen option. Fails if stdin is not a terminal.
+ */
+pub fn choose(question: &str, options: &[String]) -> Result<usize, Report> {
+    if !io::stdin().is_terminal() {
+        return Err(eyre!(
+            "{} Cannot ask interactively because stdin is not a terminal",
+            question
+        ));
+    }
+
+    let mut stderr = io::stderr();
+    writeln!(stderr, "{}", question)?;
+    for (index, option) in options.iter().enumerate() {
+        writeln!(stderr, "  {}) {}", index + 1, option)?;
+    }
+
+    loop {
+        write!(stderr, "Enter a number [1-{}]: ", options.len())?;
+        stderr.flush()?;
+
+        let mut input = String::new();
+        let read = io::stdin()
+            .lock()
+            .read_line(&mut input)
+            .wrap_err("Failed to read the choice from stdin")?;
+        if read == 0 {
+            return Err(eyre!("Aborted: no option chosen"));
+        }
+
+        match input.trim().parse::<usize>() {
+            Ok(number) if number >= 1 && number <= options.len() => return Ok(number - 1),
+            _ => writeln!(stderr, "Invalid choice '{}'", input.trim())?,
+        }
+    }
+}
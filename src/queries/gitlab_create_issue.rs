@@ -1,9 +1,7 @@
-use eyre::{Context, Report};
-use graphql_client::{GraphQLQuery, Response};
+use eyre::{ContextCompat, Report};
+use graphql_client::GraphQLQuery;
 
-use crate::errors::AppError;
-
-use super::send_graphql_request;
+use super::{check_mutation_errors, run_graphql_query};
 
 #[derive(GraphQLQuery)]
 #[graphql(
@@ -19,43 +17,33 @@ pub struct GitlabCreatedIssue {
 }
 
 pub async fn create_issue(
-    token: &String,
-    project_path: &String,
-    title: &String,
+    host: &str,
+    token: &str,
+    project_path: &str,
+    title: &str,
     description: &Option<String>,
+    labels: &[String],
 ) -> Result<GitlabCreatedIssue, Report> {
-    let query_body = GitlabCreateIssue::build_query(gitlab_create_issue::Variables {
-        project_path: project_path.to_owned(),
-        title: title.to_owned(),
-        description: description.to_owned(),
-    });
-
-    let res = send_graphql_request(token, &query_body)
-        .await
-        .wrap_err("API request failed")?;
-
-    let response_body: Response<gitlab_create_issue::ResponseData> = res
-        .json()
-        .await
-        .wrap_err("Failed to parse the Graphql response from the API")?;
-
-    if let Some(graphql_errs) = response_body.errors {
-        Err(AppError::GraphqlError {
-            message: String::from("Gitlab returned an error while creating the issue"),
-            details: format!("{:?}", graphql_errs[0]),
-        })?
-    } else {
-        // my god why! is! every! level! optional!
-        let issue = response_body
-            .data
-            .unwrap()
-            .create_issue
-            .unwrap()
-            .issue
-            .unwrap();
-        Ok(GitlabCreatedIssue {
-            iid: issue.id,
-            web_url: issue.web_url,
-        })
-    }
+    let message = "Gitlab returned an error while creating the issue";
+    let data = run_graphql_query::<GitlabCreateIssue>(
+        host,
+        token,
+        gitlab_create_issue::Variables {
+            project_path: project_path.to_owned(),
+            title: title.to_owned(),
+            description: description.clone(),
+            labels: Some(labels.to_vec()),
+        },
+        message,
+    )
+    .await?;
+
+    let payload = data.create_issue.wrap_err("missing createIssue payload")?;
+    check_mutation_errors(&payload.errors, message)?;
+
+    let issue = payload.issue.wrap_err("missing issue")?;
+    Ok(GitlabCreatedIssue {
+        iid: issue.iid,
+        web_url: issue.web_url,
+    })
 }
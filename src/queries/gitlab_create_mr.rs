@@ -0,0 +1,56 @@
+use eyre::{ContextCompat, Report};
+use graphql_client::GraphQLQuery;
+
+use super::{check_mutation_errors, run_graphql_query};
+
+#[derive(GraphQLQuery)]
+#[graphql(
+    schema_path = "graphql/schemas/gitlab.graphql",
+    query_path = "graphql/queries/gitlab_create_mr.graphql",
+    response_derives = "Debug"
+)]
+pub struct GitlabCreateMR;
+
+pub struct GitlabCreatedMR {
+    pub iid: String,
+    pub web_url: String,
+}
+
+/**
+ * Creates a new merge request from the given source branch into the given target branch.
+ */
+pub async fn create_merge_request(
+    host: &str,
+    token: &str,
+    project_path: &str,
+    source_branch: &str,
+    target_branch: &str,
+    title: &str,
+    description: &Option<String>,
+) -> Result<GitlabCreatedMR, Report> {
+    let message = "Gitlab returned an error while creating the merge request";
+    let data = run_graphql_query::<GitlabCreateMR>(
+        host,
+        token,
+        gitlab_create_mr::Variables {
+            project_path: project_path.to_string(),
+            source_branch: source_branch.to_string(),
+            target_branch: target_branch.to_string(),
+            title: title.to_string(),
+            description: description.clone(),
+        },
+        message,
+    )
+    .await?;
+
+    let payload = data
+        .merge_request_create
+        .wrap_err("missing mergeRequestCreate payload")?;
+    check_mutation_errors(&payload.errors, message)?;
+
+    let mr = payload.merge_request.wrap_err("missing merge request")?;
+    Ok(GitlabCreatedMR {
+        iid: mr.iid,
+        web_url: mr.web_url.unwrap_or_default(),
+    })
+}
@@ -0,0 +1,53 @@
+use eyre::{Context, ContextCompat, Report};
+use graphql_client::{GraphQLQuery, Response};
+
+use crate::errors::AppError;
+
+use super::send_graphql_request;
+
+#[derive(GraphQLQuery)]
+#[graphql(
+    schema_path = "graphql/schemas/gitlab.graphql",
+    query_path = "graphql/queries/gitlab_get_default_branch.graphql",
+    response_derives = "Debug"
+)]
+pub struct GitlabGetDefaultBranch;
+
+/**
+ * Fetches the name of the default branch of the given project.
+ */
+pub async fn get_default_branch(
+    host: &str,
+    token: &str,
+    project_path: &str,
+) -> Result<String, Report> {
+    let query_body = GitlabGetDefaultBranch::build_query(gitlab_get_default_branch::Variables {
+        project_path: project_path.to_string(),
+    });
+
+    let res = send_graphql_request(host, token, &query_body)
+        .await
+        .wrap_err("Failed to fetch the project details from Gitlab.")?;
+
+    let response_body: Response<gitlab_get_default_branch::ResponseData> = res
+        .json()
+        .await
+        .wrap_err("Failed to parse the project details from Gitlab.")?;
+
+    if let Some(graphql_errs) = response_body.errors {
+        Err(AppError::GraphqlError {
+            message: String::from("Gitlab returned an error while fetching the project details"),
+            details: format!("{:?}", graphql_errs[0]),
+        })?
+    } else {
+        response_body
+            .data
+            .wrap_err("missing data")?
+            .project
+            .wrap_err("missing project")?
+            .repository
+            .wrap_err("missing repository")?
+            .root_ref
+            .wrap_err("missing default branch")
+    }
+}
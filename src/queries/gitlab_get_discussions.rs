@@ -0,0 +1,122 @@
+use super::run_graphql_query;
+use eyre::{ContextCompat, Report};
+use graphql_client::GraphQLQuery;
+
+use crate::forges::{Discussion, Note};
+
+type Time = String;
+
+#[derive(GraphQLQuery)]
+#[graphql(
+    schema_path = "graphql/schemas/gitlab.graphql",
+    query_path = "graphql/queries/gitlab_get_mr_discussions.graphql",
+    response_derives = "Debug"
+)]
+pub struct GitlabGetMRDiscussions;
+
+#[derive(GraphQLQuery)]
+#[graphql(
+    schema_path = "graphql/schemas/gitlab.graphql",
+    query_path = "graphql/queries/gitlab_get_issue_discussions.graphql",
+    response_derives = "Debug"
+)]
+pub struct GitlabGetIssueDiscussions;
+
+/**
+ * Converts the discussion connection of a merge request or issue to discussions.
+ * The generated types of both queries have the same shape but are distinct, hence the macro.
+ * System notes, e.g. 'added 1 commit', are skipped, as are discussions consisting only of them.
+ */
+macro_rules! to_discussions {
+    ($discussions:expr) => {
+        $discussions
+            .nodes
+            .unwrap_or_default()
+            .into_iter()
+            .flatten()
+            .map(|discussion| Discussion {
+                resolved: Some(discussion.resolved).filter(|_| discussion.resolvable),
+                notes: discussion
+                    .notes
+                    .nodes
+                    .unwrap_or_default()
+                    .into_iter()
+                    .flatten()
+                    .filter(|note| !note.system)
+                    .map(|note| Note {
+                        author: note.author.username,
+                        body: note.body,
+                        created_at: note.created_at,
+                        position: note.position.map(|position| {
+                            match position.new_line.or(position.old_line) {
+                                Some(line) => format!("{}:{}", position.file_path, line),
+                                None => position.file_path,
+                            }
+                        }),
+                    })
+                    .collect::<Vec<Note>>(),
+            })
+            .filter(|discussion| !discussion.notes.is_empty())
+            .collect::<Vec<Discussion>>()
+    };
+}
+
+/**
+ * Fetches the discussions of the merge request with the given iid of the given project.
+ */
+pub async fn get_mr_discussions(
+    host: &str,
+    token: &str,
+    project_path: &str,
+    iid: &str,
+) -> Result<Vec<Discussion>, Report> {
+    let data = run_graphql_query::<GitlabGetMRDiscussions>(
+        host,
+        token,
+        gitlab_get_mr_discussions::Variables {
+            project_path: project_path.to_string(),
+            iid: iid.to_string(),
+        },
+        "Gitlab returned an error while fetching the discussions of the merge request",
+    )
+    .await?;
+
+    let discussions = data
+        .project
+        .wrap_err("missing project")?
+        .merge_request
+        .wrap_err_with(|| format!("Merge request {} not found", iid))?
+        .discussions;
+
+    Ok(to_discussions!(discussions))
+}
+
+/**
+ * Fetches the discussions of the issue with the given iid of the given project.
+ */
+pub async fn get_issue_discussions(
+    host: &str,
+    token: &str,
+    project_path: &str,
+    iid: &str,
+) -> Result<Vec<Discussion>, Report> {
+    let data = run_graphql_query::<GitlabGetIssueDiscussions>(
+        host,
+        token,
+        gitlab_get_issue_discussions::Variables {
+            project_path: project_path.to_string(),
+            iid: iid.to_string(),
+        },
+        "Gitlab returned an error while fetching the discussions of the issue",
+    )
+    .await?;
+
+    let discussions = data
+        .project
+        .wrap_err("missing project")?
+        .issue
+        .wrap_err_with(|| format!("Issue {} not found", iid))?
+        .discussions;
+
+    Ok(to_discussions!(discussions))
+}
@@ -0,0 +1,77 @@
+use super::run_graphql_query;
+use eyre::{ContextCompat, Report};
+use graphql_client::GraphQLQuery;
+
+#[derive(GraphQLQuery)]
+#[graphql(
+    schema_path = "graphql/schemas/gitlab.graphql",
+    query_path = "graphql/queries/gitlab_get_issue.graphql",
+    response_derives = "Debug"
+)]
+pub struct GitlabGetIssue;
+
+#[derive(Debug)]
+pub struct FetchedIssue {
+    pub iid: String,
+    pub title: String,
+    pub description: String,
+    pub web_url: String,
+    pub author: String,
+    pub state: String,
+    pub labels: Vec<String>,
+    pub assignees: Vec<String>,
+    pub milestone: Option<String>,
+}
+
+/**
+ * Fetches the issue with the given iid of the given project.
+ */
+pub async fn get_issue(
+    host: &str,
+    token: &str,
+    project_path: &str,
+    iid: &str,
+) -> Result<FetchedIssue, Report> {
+    let data = run_graphql_query::<GitlabGetIssue>(
+        host,
+        token,
+        gitlab_get_issue::Variables {
+            project_path: project_path.to_string(),
+            iid: iid.to_string(),
+        },
+        "Gitlab returned an error while fetching the issue",
+    )
+    .await?;
+
+    let issue = data
+        .project
+        .wrap_err("missing project")?
+        .issue
+        .wrap_err_with(|| format!("Issue {} not found", iid))?;
+
+    Ok(FetchedIssue {
+        iid: issue.iid,
+        title: issue.title,
+        description: issue.description.unwrap_or_default(),
+        web_url: issue.web_url,
+        author: issue.author.username,
+        state: format!("{:?}", issue.state),
+        labels: issue
+            .labels
+            .and_then(|labels| labels.nodes)
+            .unwrap_or_default()
+            .into_iter()
+            .flatten()
+            .map(|label| label.title)
+            .collect(),
+        assignees: issue
+            .assignees
+            .and_then(|assignees| assignees.nodes)
+            .unwrap_or_default()
+            .into_iter()
+            .flatten()
+            .map(|user| user.username)
+            .collect(),
+        milestone: issue.milestone.map(|milestone| milestone.title),
+    })
+}
@@ -0,0 +1,50 @@
+use super::run_graphql_query;
+use eyre::{eyre, ContextCompat, Report};
+use graphql_client::GraphQLQuery;
+
+#[derive(GraphQLQuery)]
+#[graphql(
+    schema_path = "graphql/schemas/gitlab.graphql",
+    query_path = "graphql/queries/gitlab_get_labels.graphql",
+    response_derives = "Debug"
+)]
+pub struct GitlabGetLabels;
+
+/**
+ * Returns the global IDs of the labels with the given titles, which are available in the given project
+ * or one of its ancestor groups. Fails if one of the labels does not exist.
+ */
+pub async fn get_label_ids(
+    host: &str,
+    token: &str,
+    project_path: &str,
+    titles: &[String],
+) -> Result<Vec<String>, Report> {
+    let mut ids = vec![];
+    for title in titles {
+        let data = run_graphql_query::<GitlabGetLabels>(
+            host,
+            token,
+            gitlab_get_labels::Variables {
+                project_path: project_path.to_string(),
+                search: title.clone(),
+            },
+            "Gitlab returned an error while fetching the labels",
+        )
+        .await?;
+
+        let label = data
+            .project
+            .wrap_err("missing project")?
+            .labels
+            .and_then(|labels| labels.nodes)
+            .unwrap_or_default()
+            .into_iter()
+            .flatten()
+            .find(|label| &label.title == title)
+            .ok_or_else(|| eyre!("Unknown label '{}'", title))?;
+        ids.push(label.id);
+    }
+
+    Ok(ids)
+}
@@ -0,0 +1,46 @@
+use super::run_graphql_query;
+use eyre::{eyre, ContextCompat, Report};
+use graphql_client::GraphQLQuery;
+
+#[derive(GraphQLQuery)]
+#[graphql(
+    schema_path = "graphql/schemas/gitlab.graphql",
+    query_path = "graphql/queries/gitlab_get_milestones.graphql",
+    response_derives = "Debug"
+)]
+pub struct GitlabGetMilestones;
+
+/**
+ * Returns the global ID of the milestone with the given title, which is available in the given project
+ * or one of its ancestor groups. Fails if the milestone does not exist.
+ */
+pub async fn get_milestone_id(
+    host: &str,
+    token: &str,
+    project_path: &str,
+    title: &str,
+) -> Result<String, Report> {
+    let data = run_graphql_query::<GitlabGetMilestones>(
+        host,
+        token,
+        gitlab_get_milestones::Variables {
+            project_path: project_path.to_string(),
+            title: title.to_string(),
+        },
+        "Gitlab returned an error while fetching the milestones",
+    )
+    .await?;
+
+    let milestone = data
+        .project
+        .wrap_err("missing project")?
+        .milestones
+        .and_then(|milestones| milestones.nodes)
+        .unwrap_or_default()
+        .into_iter()
+        .flatten()
+        .find(|milestone| milestone.title == title)
+        .ok_or_else(|| eyre!("Unknown milestone '{}'", title))?;
+
+    Ok(milestone.id)
+}
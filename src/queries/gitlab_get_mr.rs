@@ -1,8 +1,6 @@
-use crate::errors::AppError;
-
-use super::send_graphql_request;
-use eyre::{eyre, Context, ContextCompat, Report};
-use graphql_client::{GraphQLQuery, Response};
+use super::run_graphql_query;
+use eyre::{Context, ContextCompat, Report};
+use graphql_client::GraphQLQuery;
 
 #[derive(GraphQLQuery)]
 #[graphql(
@@ -16,69 +14,145 @@ pub struct GitlabGetMR;
 pub struct FetchedMergeRequest {
     pub project_path: String,
     pub iid: String,
+    pub title: String,
     pub description: String,
     pub web_url: String,
+    pub author: String,
+    pub state: String,
+    pub source_branch: String,
+    pub target_branch: String,
+    /// path of the project the merge request is opened from, `None` if it has been deleted
+    pub source_project_path: Option<String>,
+    pub draft: bool,
+    pub labels: Vec<String>,
+    pub assignees: Vec<String>,
+    pub reviewers: Vec<String>,
+    pub milestone: Option<String>,
 }
 
-fn parse_mr_response(
-    project_path: &String,
-    data: Response<gitlab_get_mr::ResponseData>,
-) -> Result<FetchedMergeRequest, Report> {
-    let project = data
-        .data
-        .wrap_err("missing data")?
-        .project
-        .wrap_err("missing project")?;
+fn parse_mr(
+    project_path: &str,
+    mr: gitlab_get_mr::GitlabGetMrProjectMergeRequestsNodes,
+) -> FetchedMergeRequest {
+    let labels = mr
+        .labels
+        .and_then(|labels| labels.nodes)
+        .unwrap_or_default()
+        .into_iter()
+        .flatten()
+        .map(|label| label.title)
+        .collect();
+    let assignees = mr
+        .assignees
+        .and_then(|assignees| assignees.nodes)
+        .unwrap_or_default()
+        .into_iter()
+        .flatten()
+        .map(|user| user.username)
+        .collect();
+    let reviewers = mr
+        .reviewers
+        .and_then(|reviewers| reviewers.nodes)
+        .unwrap_or_default()
+        .into_iter()
+        .flatten()
+        .map(|user| user.username)
+        .collect();
+
+    FetchedMergeRequest {
+        project_path: project_path.to_string(),
+        iid: mr.iid,
+        title: mr.title,
+        description: mr.description.unwrap_or_default(),
+        web_url: mr.web_url.unwrap_or_default(),
+        author: mr.author.map(|author| author.username).unwrap_or_default(),
+        state: format!("{:?}", mr.state),
+        source_branch: mr.source_branch,
+        target_branch: mr.target_branch,
+        source_project_path: mr.source_project.map(|project| project.full_path),
+        draft: mr.draft,
+        labels,
+        assignees,
+        reviewers,
+        milestone: mr.milestone.map(|milestone| milestone.title),
+    }
+}
+
+fn parse_mrs_response(
+    project_path: &str,
+    data: gitlab_get_mr::ResponseData,
+) -> Result<Vec<FetchedMergeRequest>, Report> {
+    let project = data.project.wrap_err("missing project")?;
 
     let mrs_opt = project
         .merge_requests
         .wrap_err("Missing merge requests")?
         .nodes
         .wrap_err("Missing merge requests")?;
-    let first_mr_opt = mrs_opt.first().wrap_err("Missing merge request")?;
 
-    match first_mr_opt {
-        None => Err(eyre!("Missing merge request")),
-        Some(v) => Ok(FetchedMergeRequest {
-            project_path: project_path.clone(),
-            iid: v.iid.clone(),
-            description: v.description.clone().unwrap_or_default(),
-            web_url: v.web_url.clone().unwrap_or_default(),
-        }),
-    }
+    Ok(mrs_opt
+        .into_iter()
+        .flatten()
+        .map(|mr| parse_mr(project_path, mr))
+        .collect())
 }
 
 /**
- * Fetches the merge request of the given project related to the given branch name.
+ * Fetches the merge requests matching the given variables, i.e. either by source branch or by iid,
+ * most recently created first.
  */
-pub async fn get_merge_request(
-    token: &String,
-    project_path: &String,
-    branch_name: &String,
-) -> Result<FetchedMergeRequest, Report> {
-    let query_body = GitlabGetMR::build_query(gitlab_get_mr::Variables {
-        project_path: project_path.clone(),
-        branch_name: branch_name.clone(),
-    });
+async fn fetch_merge_requests(
+    host: &str,
+    token: &str,
+    variables: gitlab_get_mr::Variables,
+) -> Result<Vec<FetchedMergeRequest>, Report> {
+    let project_path = variables.project_path.clone();
+    let data = run_graphql_query::<GitlabGetMR>(
+        host,
+        token,
+        variables,
+        "Gitlab returned an error while fetching the merge request details",
+    )
+    .await?;
 
-    let res = send_graphql_request(token, &query_body)
-        .await
-        .wrap_err_with(|| format!("Failed to fetch the merge request details from Gitlab."))?;
+    parse_mrs_response(&project_path, data)
+        .wrap_err("Failed to get merge request details from graphql response")
+}
 
-    let response_body: Response<gitlab_get_mr::ResponseData> = res
-        .json()
-        .await
-        .wrap_err_with(|| format!("Failed to parse the merge request details from Gitlab."))?;
+/**
+ * Fetches all merge requests of the given project with the given source branch, regardless of their state.
+ */
+pub async fn get_merge_requests(
+    host: &str,
+    token: &str,
+    project_path: &str,
+    branch_name: &str,
+) -> Result<Vec<FetchedMergeRequest>, Report> {
+    let variables = gitlab_get_mr::Variables {
+        project_path: project_path.to_string(),
+        branch_names: Some(vec![branch_name.to_string()]),
+        iids: None,
+    };
+    fetch_merge_requests(host, token, variables).await
+}
 
-    if let Some(graphql_errs) = response_body.errors {
-        Err(AppError::GraphqlError {
-            message: String::from(
-                "Gitlab returned an error while fetching the merge request details",
-            ),
-            details: format!("{:?}", graphql_errs[0]),
-        })?
-    } else {
-        parse_mr_response(project_path, response_body)
-            .wrap_err("Failed to get merge request details from graphql response")
-    }
+/**
+ * Fetches the merge request of the given project with the given iid.
+ */
+pub async fn get_merge_request_by_iid(
+    host: &str,
+    token: &str,
+    project_path: &str,
+    iid: &str,
+) -> Result<FetchedMergeRequest, Report> {
+    let variables = gitlab_get_mr::Variables {
+        project_path: project_path.to_string(),
+        branch_names: None,
+        iids: Some(vec![iid.to_string()]),
+    };
+    fetch_merge_requests(host, token, variables)
+        .await?
+        .into_iter()
+        .next()
+        .wrap_err_with(|| format!("Missing merge request {}", iid))
 }
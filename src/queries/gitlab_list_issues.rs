@@ -0,0 +1,110 @@
+use super::run_graphql_query;
+use eyre::{ContextCompat, Report};
+use graphql_client::GraphQLQuery;
+
+use crate::forges::{IssueFilter, IssueState, IssueSummary};
+
+type Time = String;
+
+#[derive(GraphQLQuery)]
+#[graphql(
+    schema_path = "graphql/schemas/gitlab.graphql",
+    query_path = "graphql/queries/gitlab_list_issues.graphql",
+    response_derives = "Debug"
+)]
+pub struct GitlabListIssues;
+
+/// maximum number of issues Gitlab returns per page
+const MAX_PAGE_SIZE: usize = 100;
+
+/**
+ * A page of issues and the cursor of the next page, if there is one.
+ */
+#[derive(Debug)]
+pub struct IssuePage {
+    pub issues: Vec<IssueSummary>,
+    pub next_cursor: Option<String>,
+}
+
+/**
+ * Fetches the page of issues of the given project after the given cursor, most recently updated first.
+ * Only the state, labels, assignee, milestone and search text of the filter are applied by Gitlab.
+ */
+pub async fn list_issues_page(
+    host: &str,
+    token: &str,
+    project_path: &str,
+    filter: &IssueFilter,
+    after: Option<String>,
+) -> Result<IssuePage, Report> {
+    let state = match filter.state {
+        IssueState::Opened => gitlab_list_issues::IssuableState::opened,
+        IssueState::Closed => gitlab_list_issues::IssuableState::closed,
+        IssueState::All => gitlab_list_issues::IssuableState::all,
+    };
+    let data = run_graphql_query::<GitlabListIssues>(
+        host,
+        token,
+        gitlab_list_issues::Variables {
+            project_path: project_path.to_string(),
+            state: Some(state),
+            labels: Some(filter.labels.iter().cloned().map(Some).collect())
+                .filter(|labels: &Vec<_>| !labels.is_empty()),
+            assignees: filter.assignee.clone().map(|assignee| vec![assignee]),
+            milestones: filter.milestone.clone().map(|title| vec![Some(title)]),
+            search: filter.search.clone(),
+            first: Some(filter.limit.min(MAX_PAGE_SIZE) as i64),
+            after,
+        },
+        "Gitlab returned an error while listing the issues",
+    )
+    .await?;
+
+    let connection = data
+        .project
+        .wrap_err("missing project")?
+        .issues
+        .wrap_err("Missing issues")?;
+
+    let issues = connection
+        .nodes
+        .unwrap_or_default()
+        .into_iter()
+        .flatten()
+        .map(|issue| IssueSummary {
+            iid: issue.iid,
+            title: issue.title,
+            state: format!("{:?}", issue.state),
+            author: issue.author.username,
+            assignees: issue
+                .assignees
+                .and_then(|assignees| assignees.nodes)
+                .unwrap_or_default()
+                .into_iter()
+                .flatten()
+                .map(|user| user.username)
+                .collect(),
+            labels: issue
+                .labels
+                .and_then(|labels| labels.nodes)
+                .unwrap_or_default()
+                .into_iter()
+                .flatten()
+                .map(|label| label.title)
+                .collect(),
+            milestone: issue.milestone.map(|milestone| milestone.title),
+            due_date: issue.due_date,
+            confidential: issue.confidential,
+            updated_at: issue.updated_at,
+            web_url: issue.web_url,
+        })
+        .collect();
+    let next_cursor = Some(connection.page_info)
+        .filter(|page_info| page_info.has_next_page)
+        .and_then(|page_info| page_info.end_cursor);
+
+    Ok(IssuePage {
+        issues,
+        next_cursor,
+    })
+}
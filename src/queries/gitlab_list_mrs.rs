@@ -0,0 +1,95 @@
+use super::run_graphql_query;
+use eyre::{ContextCompat, Report};
+use graphql_client::GraphQLQuery;
+
+use crate::forges::{MergeRequestFilter, MergeRequestState, MergeRequestSummary};
+
+type Time = String;
+
+#[derive(GraphQLQuery)]
+#[graphql(
+    schema_path = "graphql/schemas/gitlab.graphql",
+    query_path = "graphql/queries/gitlab_list_mrs.graphql",
+    response_derives = "Debug"
+)]
+pub struct GitlabListMRs;
+
+/// maximum number of merge requests Gitlab returns per page
+const MAX_PAGE_SIZE: usize = 100;
+
+/**
+ * A page of merge requests and the cursor of the next page, if there is one.
+ */
+#[derive(Debug)]
+pub struct MergeRequestPage {
+    pub merge_requests: Vec<MergeRequestSummary>,
+    pub next_cursor: Option<String>,
+}
+
+/**
+ * Fetches the page of merge requests of the given project after the given cursor, most recently updated first.
+ * Gitlab cannot filter by draft status, so the draft filter is not applied.
+ */
+pub async fn list_mrs_page(
+    host: &str,
+    token: &str,
+    project_path: &str,
+    filter: &MergeRequestFilter,
+    after: Option<String>,
+) -> Result<MergeRequestPage, Report> {
+    let state = match filter.state {
+        MergeRequestState::Opened => gitlab_list_m_rs::MergeRequestState::opened,
+        MergeRequestState::Merged => gitlab_list_m_rs::MergeRequestState::merged,
+        MergeRequestState::Closed => gitlab_list_m_rs::MergeRequestState::closed,
+        MergeRequestState::All => gitlab_list_m_rs::MergeRequestState::all,
+    };
+    let data = run_graphql_query::<GitlabListMRs>(
+        host,
+        token,
+        gitlab_list_m_rs::Variables {
+            project_path: project_path.to_string(),
+            state: Some(state),
+            author: filter.author.clone(),
+            assignee: filter.assignee.clone(),
+            reviewer: filter.reviewer.clone(),
+            labels: Some(filter.labels.clone()).filter(|labels| !labels.is_empty()),
+            target_branches: filter.target_branch.clone().map(|branch| vec![branch]),
+            first: Some(filter.limit.min(MAX_PAGE_SIZE) as i64),
+            after,
+        },
+        "Gitlab returned an error while listing the merge requests",
+    )
+    .await?;
+
+    let connection = data
+        .project
+        .wrap_err("missing project")?
+        .merge_requests
+        .wrap_err("Missing merge requests")?;
+
+    let merge_requests = connection
+        .nodes
+        .unwrap_or_default()
+        .into_iter()
+        .flatten()
+        .map(|mr| MergeRequestSummary {
+            iid: mr.iid,
+            title: mr.title,
+            author: mr.author.map(|author| author.username).unwrap_or_default(),
+            draft: mr.draft,
+            pipeline_status: mr
+                .head_pipeline
+                .map(|pipeline| format!("{:?}", pipeline.status).to_lowercase()),
+            updated_at: mr.updated_at,
+            web_url: mr.web_url.unwrap_or_default(),
+        })
+        .collect();
+    let next_cursor = Some(connection.page_info)
+        .filter(|page_info| page_info.has_next_page)
+        .and_then(|page_info| page_info.end_cursor);
+
+    Ok(MergeRequestPage {
+        merge_requests,
+        next_cursor,
+    })
+}
@@ -0,0 +1,40 @@
+use super::{check_mutation_errors, run_graphql_query};
+use eyre::{ContextCompat, Report};
+use graphql_client::GraphQLQuery;
+
+#[derive(GraphQLQuery)]
+#[graphql(
+    schema_path = "graphql/schemas/gitlab.graphql",
+    query_path = "graphql/queries/gitlab_update_issue.graphql",
+    response_derives = "Debug"
+)]
+pub struct GitlabUpdateIssue;
+
+/**
+ * Updates the title and/or description of an issue. `None` values are left unchanged.
+ */
+pub async fn update_issue(
+    host: &str,
+    token: &str,
+    project_path: &str,
+    iid: &str,
+    title: &Option<String>,
+    description: &Option<String>,
+) -> Result<(), Report> {
+    let message = "Gitlab returned an error while updating the issue";
+    let data = run_graphql_query::<GitlabUpdateIssue>(
+        host,
+        token,
+        gitlab_update_issue::Variables {
+            project_path: project_path.to_string(),
+            iid: iid.to_string(),
+            title: title.clone(),
+            description: description.clone(),
+        },
+        message,
+    )
+    .await?;
+
+    let payload = data.update_issue.wrap_err("missing updateIssue payload")?;
+    check_mutation_errors(&payload.errors, message)
+}
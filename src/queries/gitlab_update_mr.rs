@@ -0,0 +1,193 @@
+use super::{check_mutation_errors, run_graphql_query};
+use eyre::{ContextCompat, Report};
+use graphql_client::GraphQLQuery;
+
+type LabelID = String;
+type MilestoneID = String;
+
+#[derive(GraphQLQuery)]
+#[graphql(
+    schema_path = "graphql/schemas/gitlab.graphql",
+    query_path = "graphql/queries/gitlab_update_mr.graphql",
+    response_derives = "Debug"
+)]
+pub struct GitlabUpdateMR;
+
+#[derive(GraphQLQuery)]
+#[graphql(
+    schema_path = "graphql/schemas/gitlab.graphql",
+    query_path = "graphql/queries/gitlab_set_mr_labels.graphql",
+    response_derives = "Debug"
+)]
+pub struct GitlabSetMRLabels;
+
+#[derive(GraphQLQuery)]
+#[graphql(
+    schema_path = "graphql/schemas/gitlab.graphql",
+    query_path = "graphql/queries/gitlab_set_mr_assignees.graphql",
+    response_derives = "Debug"
+)]
+pub struct GitlabSetMRAssignees;
+
+#[derive(GraphQLQuery)]
+#[graphql(
+    schema_path = "graphql/schemas/gitlab.graphql",
+    query_path = "graphql/queries/gitlab_set_mr_milestone.graphql",
+    response_derives = "Debug"
+)]
+pub struct GitlabSetMRMilestone;
+
+#[derive(GraphQLQuery)]
+#[graphql(
+    schema_path = "graphql/schemas/gitlab.graphql",
+    query_path = "graphql/queries/gitlab_set_mr_draft.graphql",
+    response_derives = "Debug"
+)]
+pub struct GitlabSetMRDraft;
+
+/**
+ * Updates the title and/or description of a merge request. `None` values are left unchanged.
+ */
+pub async fn update_merge_request(
+    host: &str,
+    token: &str,
+    project_path: &str,
+    iid: &str,
+    title: &Option<String>,
+    description: &Option<String>,
+) -> Result<(), Report> {
+    let message = "Gitlab returned an error while updating the merge request";
+    let data = run_graphql_query::<GitlabUpdateMR>(
+        host,
+        token,
+        gitlab_update_mr::Variables {
+            project_path: project_path.to_string(),
+            iid: iid.to_string(),
+            title: title.clone(),
+            desc: description.clone(),
+        },
+        message,
+    )
+    .await?;
+
+    let payload = data
+        .merge_request_update
+        .wrap_err("missing mergeRequestUpdate payload")?;
+    check_mutation_errors(&payload.errors, message)
+}
+
+/**
+ * Replaces the labels of a merge request by the labels with the given IDs.
+ */
+pub async fn set_merge_request_labels(
+    host: &str,
+    token: &str,
+    project_path: &str,
+    iid: &str,
+    label_ids: &[String],
+) -> Result<(), Report> {
+    let message = "Gitlab returned an error while setting the merge request labels";
+    let data = run_graphql_query::<GitlabSetMRLabels>(
+        host,
+        token,
+        gitlab_set_mr_labels::Variables {
+            project_path: project_path.to_string(),
+            iid: iid.to_string(),
+            label_ids: label_ids.to_vec(),
+        },
+        message,
+    )
+    .await?;
+
+    let payload = data
+        .merge_request_set_labels
+        .wrap_err("missing mergeRequestSetLabels payload")?;
+    check_mutation_errors(&payload.errors, message)
+}
+
+/**
+ * Replaces the assignees of a merge request by the users with the given usernames.
+ */
+pub async fn set_merge_request_assignees(
+    host: &str,
+    token: &str,
+    project_path: &str,
+    iid: &str,
+    usernames: &[String],
+) -> Result<(), Report> {
+    let message = "Gitlab returned an error while setting the merge request assignees";
+    let data = run_graphql_query::<GitlabSetMRAssignees>(
+        host,
+        token,
+        gitlab_set_mr_assignees::Variables {
+            project_path: project_path.to_string(),
+            iid: iid.to_string(),
+            usernames: usernames.to_vec(),
+        },
+        message,
+    )
+    .await?;
+
+    let payload = data
+        .merge_request_set_assignees
+        .wrap_err("missing mergeRequestSetAssignees payload")?;
+    check_mutation_errors(&payload.errors, message)
+}
+
+/**
+ * Sets the milestone with the given ID on a merge request. `None` removes the milestone.
+ */
+pub async fn set_merge_request_milestone(
+    host: &str,
+    token: &str,
+    project_path: &str,
+    iid: &str,
+    milestone_id: &Option<String>,
+) -> Result<(), Report> {
+    let message = "Gitlab returned an error while setting the merge request milestone";
+    let data = run_graphql_query::<GitlabSetMRMilestone>(
+        host,
+        token,
+        gitlab_set_mr_milestone::Variables {
+            project_path: project_path.to_string(),
+            iid: iid.to_string(),
+            milestone_id: milestone_id.clone(),
+        },
+        message,
+    )
+    .await?;
+
+    let payload = data
+        .merge_request_set_milestone
+        .wrap_err("missing mergeRequestSetMilestone payload")?;
+    check_mutation_errors(&payload.errors, message)
+}
+
+/**
+ * Marks a merge request as draft or as ready.
+ */
+pub async fn set_merge_request_draft(
+    host: &str,
+    token: &str,
+    project_path: &str,
+    iid: &str,
+    draft: bool,
+) -> Result<(), Report> {
+    let message = "Gitlab returned an error while setting the merge request draft status";
+    let data = run_graphql_query::<GitlabSetMRDraft>(
+        host,
+        token,
+        gitlab_set_mr_draft::Variables {
+            project_path: project_path.to_string(),
+            iid: iid.to_string(),
+            draft,
+        },
+        message,
+    )
+    .await?;
+
+    let payload = data
+        .merge_request_set_draft
+        .wrap_err("missing mergeRequestSetDraft payload")?;
+    check_mutation_errors(&payload.errors, message)
+}
@@ -1,16 +1,31 @@
-use eyre::Report;
-use graphql_client::QueryBody;
+use eyre::{eyre, Context, ContextCompat, Report};
+use graphql_client::{GraphQLQuery, QueryBody};
 use log::debug;
 use reqwest::Response;
 use serde::Serialize;
-use serde_json;
+
+use crate::errors::AppError;
 
 pub mod gitlab_create_issue;
+pub mod gitlab_create_mr;
+pub mod gitlab_get_default_branch;
+pub mod gitlab_get_discussions;
+pub mod gitlab_get_issue;
+pub mod gitlab_get_labels;
+pub mod gitlab_get_milestones;
 pub mod gitlab_get_mr;
-pub mod gitlab_update_mr_desc;
+pub mod gitlab_list_issues;
+pub mod gitlab_list_mrs;
+pub mod gitlab_update_issue;
+pub mod gitlab_update_mr;
 
-async fn send_graphql_request<'de, V: Serialize>(
-    token: &String,
+/**
+ * Sends the given query to the Graphql API of the Gitlab instance at the given host,
+ * e.g. 'https://gitlab.com'.
+ */
+async fn send_graphql_request<V: Serialize>(
+    host: &str,
+    token: &str,
     query_body: &QueryBody<V>,
 ) -> Result<Response, Report> {
     let client = reqwest::Client::new();
@@ -21,7 +36,7 @@ async fn send_graphql_request<'de, V: Serialize>(
     );
 
     let res = client
-        .post("https://gitlab.com/api/graphql")
+        .post(format!("{}/api/graphql", host))
         .header("Authorization", format!("Bearer {}", token))
         .json(query_body)
         .send()
@@ -29,3 +44,45 @@ async fn send_graphql_request<'de, V: Serialize>(
 
     Ok(res)
 }
+
+/**
+ * Runs the given Graphql query against the Gitlab instance at the given host and returns its data.
+ * Errors returned by the API are reported as `AppError::GraphqlError` using the given error message.
+ */
+async fn run_graphql_query<Q: GraphQLQuery>(
+    host: &str,
+    token: &str,
+    variables: Q::Variables,
+    error_message: &str,
+) -> Result<Q::ResponseData, Report> {
+    let query_body = Q::build_query(variables);
+
+    let res = send_graphql_request(host, token, &query_body)
+        .await
+        .wrap_err("API request failed")?;
+
+    let response_body: graphql_client::Response<Q::ResponseData> = res
+        .json()
+        .await
+        .wrap_err("Failed to parse the Graphql response from the API")?;
+
+    if let Some(graphql_errs) = response_body.errors {
+        Err(AppError::GraphqlError {
+            message: String::from(error_message),
+            details: format!("{:?}", graphql_errs[0]),
+        })?
+    } else {
+        response_body.data.wrap_err("missing data")
+    }
+}
+
+/**
+ * Fails with the given error message if the payload of a mutation contains errors.
+ */
+fn check_mutation_errors(errors: &[String], error_message: &str) -> Result<(), Report> {
+    if errors.is_empty() {
+        Ok(())
+    } else {
+        Err(eyre!("{}: {}", error_message, errors.join(", ")))
+    }
+}
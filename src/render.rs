@@ -0,0 +1,489 @@
+/*!
+ * Renders markdown for reading it in the terminal, using ANSI colours.
+ * Like the built-in ORG converter, the renderer is line based and covers the markdown commonly used in
+ * issues and merge requests: headings, lists, quotes, code blocks with syntax highlighting, emphasis and links.
+ */
+
+use colored::Colorize;
+
+use crate::{convert::org::fence_marker, forges::Discussion, table::format_time};
+
+/// width of horizontal rules and separators
+const RULE_WIDTH: usize = 60;
+
+/// keywords highlighted in code blocks per language
+fn keywords(language: &str) -> &'static [&'static str] {
+    match language {
+        "rust" | "rs" => &[
+            "as", "async", "await", "break", "const", "continue", "crate", "dyn", "else", "enum",
+            "false", "fn", "for", "if", "impl", "in", "let", "loop", "match", "mod", "move", "mut",
+            "pub", "ref", "return", "self", "Self", "static", "struct", "super", "trait", "true",
+            "type", "unsafe", "use", "where", "while",
+        ],
+        "python" | "py" => &[
+            "and", "as", "assert", "async", "await", "break", "class", "continue", "def", "del",
+            "elif", "else", "except", "False", "finally", "for", "from", "if", "import", "in",
+            "is", "lambda", "None", "not", "or", "pass", "raise", "return", "True", "try", "while",
+            "with", "yield",
+        ],
+        "javascript" | "js" | "typescript" | "ts" | "jsx" | "tsx" => &[
+            "async",
+            "await",
+            "break",
+            "case",
+            "catch",
+            "class",
+            "const",
+            "continue",
+            "default",
+            "delete",
+            "else",
+            "export",
+            "extends",
+            "false",
+            "finally",
+            "for",
+            "from",
+            "function",
+            "if",
+            "import",
+            "in",
+            "instanceof",
+            "interface",
+            "let",
+            "new",
+            "null",
+            "of",
+            "return",
+            "switch",
+            "this",
+            "throw",
+            "true",
+            "try",
+            "type",
+            "typeof",
+            "undefined",
+            "var",
+            "while",
+            "yield",
+        ],
+        "go" => &[
+            "break",
+            "case",
+            "chan",
+            "const",
+            "continue",
+            "default",
+            "defer",
+            "else",
+            "false",
+            "for",
+            "func",
+            "go",
+            "if",
+            "import",
+            "interface",
+            "map",
+            "nil",
+            "package",
+            "range",
+            "return",
+            "select",
+            "struct",
+            "switch",
+            "true",
+            "type",
+            "var",
+        ],
+        "java" | "kotlin" | "kt" | "c" | "cpp" | "c++" | "cs" | "csharp" => &[
+            "break",
+            "case",
+            "catch",
+            "class",
+            "const",
+            "continue",
+            "default",
+            "do",
+            "else",
+            "enum",
+            "extends",
+            "false",
+            "final",
+            "for",
+            "fun",
+            "if",
+            "implements",
+            "import",
+            "new",
+            "null",
+            "private",
+            "protected",
+            "public",
+            "return",
+            "static",
+            "struct",
+            "switch",
+            "this",
+            "throw",
+            "true",
+            "try",
+            "val",
+            "var",
+            "void",
+            "while",
+        ],
+        "sh" | "bash" | "shell" | "zsh" | "console" => &[
+            "case", "do", "done", "elif", "else", "esac", "export", "fi", "for", "function", "if",
+            "in", "local", "return", "then", "while",
+        ],
+        "ruby" | "rb" => &[
+            "begin", "class", "def", "do", "else", "elsif", "end", "ensure", "false", "if",
+            "module", "nil", "require", "rescue", "return", "self", "true", "unless", "until",
+            "when", "while", "yield",
+        ],
+        "sql" => &[
+            "AND", "AS", "BY", "CREATE", "DELETE", "FROM", "GROUP", "INSERT", "INTO", "JOIN",
+            "LEFT", "LIMIT", "NOT", "NULL", "ON", "OR", "ORDER", "SELECT", "SET", "TABLE",
+            "UPDATE", "VALUES", "WHERE",
+        ],
+        _ => &[],
+    }
+}
+
+/// returns the marker starting a line comment in the given language, if known
+fn line_comment(language: &str) -> Option<&'static str> {
+    match language {
+        "python" | "py" | "sh" | "bash" | "shell" | "zsh" | "ruby" | "rb" | "yaml" | "yml"
+        | "toml" => Some("#"),
+        "sql" => Some("--"),
+        "" | "text" | "plain" | "markdown" | "md" | "json" => None,
+        _ => Some("//"),
+    }
+}
+
+/**
+ * Highlights a single line of code in the given language.
+ * Strings, numbers, comments and keywords are coloured. For unknown languages, C-like comments are assumed
+ * and no keywords are highlighted.
+ */
+fn highlight(line: &str, language: &str) -> String {
+    if language == "diff" || language == "patch" {
+        return match line.chars().next() {
+            Some('+') => line.green().to_string(),
+            Some('-') => line.red().to_string(),
+            Some('@') => line.cyan().to_string(),
+            _ => line.to_string(),
+        };
+    }
+    if language.is_empty() || language == "text" || language == "plain" {
+        return line.to_string();
+    }
+
+    let keywords = keywords(language);
+    let comment = line_comment(language);
+    let mut out = String::new();
+    let mut rest = line;
+    while let Some(c) = rest.chars().next() {
+        if comment.is_some_and(|marker| rest.starts_with(marker)) {
+            out += &rest.dimmed().to_string();
+            break;
+        } else if c == '"' || c == '\'' || c == '`' {
+            // strings end at the next unescaped quote or the end of the line:
+            let mut end = rest.len();
+            let mut escaped = false;
+            for (i, next) in rest.char_indices().skip(1) {
+                if next == c && !escaped {
+                    end = i + next.len_utf8();
+                    break;
+                }
+                escaped = next == '\\' && !escaped;
+            }
+            out += &rest[..end].green().to_string();
+            rest = &rest[end..];
+        } else if c.is_alphanumeric() || c == '_' {
+            let end = rest
+                .find(|next: char| !(next.is_alphanumeric() || next == '_'))
+                .unwrap_or(rest.len());
+            let word = &rest[..end];
+            if keywords.contains(&word) {
+                out += &word.magenta().to_string();
+            } else if c.is_ascii_digit() {
+                out += &word.cyan().to_string();
+            } else {
+                out += word;
+            }
+            rest = &rest[end..];
+        } else {
+            out.push(c);
+            rest = &rest[c.len_utf8()..];
+        }
+    }
+
+    out
+}
+
+/// returns the text between the given delimiters at the start of `text` and the rest after the closing delimiter
+fn delimited<'a>(text: &'a str, open: &str, close: &str) -> Option<(&'a str, &'a str)> {
+    let inner = text.strip_prefix(open)?;
+    let end = inner.find(close)?;
+    Some((&inner[..end], &inner[end + close.len()..]))
+        .filter(|(inner, _)| !inner.is_empty() && !inner.starts_with(' ') && !inner.ends_with(' '))
+}
+
+/// parses a link '[text](url)' at the start of the given text, returns the text, the URL and the rest
+fn link(text: &str) -> Option<(&str, &str, &str)> {
+    let (label, rest) = delimited(text, "[", "](")?;
+    let end = rest.find(')')?;
+    Some((label, &rest[..end], &rest[end + 1..]))
+}
+
+/**
+ * Renders the inline markdown of a single line: code spans, emphasis, strikethrough and links.
+ * Emphasis is not nested, the text inside of it is shown as is.
+ */
+fn render_inline(text: &str) -> String {
+    let mut out = String::new();
+    let mut rest = text;
+    let mut previous: Option<char> = None;
+    while let Some(c) = rest.chars().next() {
+        // underscores inside words, e.g. in 'snake_case', do not start emphasis:
+        let word_boundary = previous.is_none_or(|previous| !previous.is_alphanumeric());
+        let (rendered, next) = if let Some((code, next)) = delimited(rest, "`", "`") {
+            (code.yellow().to_string(), next)
+        } else if let Some((alt, url, next)) = rest.strip_prefix('!').and_then(link) {
+            (
+                format!("{} {}", format!("[{}]", alt).italic(), url.dimmed()),
+                next,
+            )
+        } else if let Some((label, url, next)) = link(rest) {
+            if label == url {
+                (url.blue().underline().to_string(), next)
+            } else {
+                (
+                    format!(
+                        "{} {}",
+                        label.blue().underline(),
+                        format!("({})", url).dimmed()
+                    ),
+                    next,
+                )
+            }
+        } else if let Some((url, next)) = delimited(rest, "<", ">")
+            .filter(|(url, _)| url.starts_with("http://") || url.starts_with("https://"))
+        {
+            (url.blue().underline().to_string(), next)
+        } else if let Some((strong, next)) = delimited(rest, "**", "**")
+            .or_else(|| delimited(rest, "__", "__").filter(|_| word_boundary))
+        {
+            (strong.bold().to_string(), next)
+        } else if let Some((deleted, next)) = delimited(rest, "~~", "~~") {
+            (deleted.strikethrough().to_string(), next)
+        } else if let Some((emphasis, next)) = delimited(rest, "*", "*")
+            .or_else(|| delimited(rest, "_", "_").filter(|_| word_boundary))
+        {
+            (emphasis.italic().to_string(), next)
+        } else {
+            (c.to_string(), &rest[c.len_utf8()..])
+        };
+
+        out += &rendered;
+        previous = rest[..rest.len() - next.len()].chars().last();
+        rest = next;
+    }
+
+    out
+}
+
+/// splits a list item into its indentation, its marker and its text, e.g. '  - [x] done'
+fn list_item(line: &str) -> Option<(&str, String, &str)> {
+    let text = line.trim_start();
+    let indent = &line[..line.len() - text.len()];
+    let (marker, rest) = if let Some(rest) = text
+        .strip_prefix("- ")
+        .or_else(|| text.strip_prefix("* "))
+        .or_else(|| text.strip_prefix("+ "))
+    {
+        (String::from("•"), rest)
+    } else {
+        let digits = text.find(|c: char| !c.is_ascii_digit())?;
+        let rest = text[digits..]
+            .strip_prefix(". ")
+            .or_else(|| text[digits..].strip_prefix(") "))
+            .filter(|_| digits > 0)?;
+        (format!("{}.", &text[..digits]), rest)
+    };
+
+    let (marker, rest) = if let Some(rest) = rest.strip_prefix("[ ] ") {
+        (format!("{} ☐", marker), rest)
+    } else if let Some(rest) = rest
+        .strip_prefix("[x] ")
+        .or_else(|| rest.strip_prefix("[X] "))
+    {
+        (format!("{} ☑", marker), rest)
+    } else {
+        (marker, rest)
+    };
+
+    Some((indent, marker, rest))
+}
+
+/// returns whether the given line is a horizontal rule, e.g. '---'
+fn is_rule(line: &str) -> bool {
+    let line: String = line.chars().filter(|c| !c.is_whitespace()).collect();
+    line.len() >= 3
+        && ["-", "*", "_"]
+            .iter()
+            .any(|marker| line.chars().all(|c| c.to_string() == *marker))
+}
+
+/**
+ * Renders the given markdown for the terminal.
+ */
+pub fn render_markdown(markdown: &str) -> String {
+    let mut lines: Vec<String> = vec![];
+    // the marker and language of the current code block, if any:
+    let mut fence: Option<(String, String)> = None;
+    let mut in_comment = false;
+
+    for line in markdown.lines() {
+        if let Some((marker, language)) = &fence {
+            // the closing fence consists of at least as many of the same characters as the opening one:
+            let closing = fence_marker(line.trim()).filter(|closing| {
+                closing.starts_with(marker.as_str()) && closing.len() == line.trim().len()
+            });
+            if closing.is_some() {
+                fence = None;
+            } else {
+                lines.push(format!("    {}", highlight(line, language)));
+            }
+            continue;
+        }
+        if in_comment || line.trim_start().starts_with("<!--") {
+            in_comment = !line.contains("-->");
+            continue;
+        }
+
+        let trimmed = line.trim_start();
+        if let Some(marker) = fence_marker(trimmed) {
+            let language = trimmed[marker.len()..].trim().to_lowercase();
+            if !language.is_empty() {
+                lines.push(format!("    {}", language.dimmed()));
+            }
+            fence = Some((marker, language));
+        } else if let Some(heading) = trimmed
+            .strip_prefix('#')
+            .filter(|_| trimmed.len() > 1)
+            .map(|_| trimmed.trim_start_matches('#'))
+            .filter(|heading| heading.starts_with(' ') || heading.is_empty())
+        {
+            let level = trimmed.len() - heading.len();
+            let heading = render_inline(heading.trim().trim_end_matches('#').trim_end());
+            lines.push(match level {
+                1 => heading.bold().underline().magenta().to_string(),
+                2 => heading.bold().magenta().to_string(),
+                _ => heading.bold().to_string(),
+            });
+        } else if is_rule(line) {
+            lines.push("─".repeat(RULE_WIDTH).dimmed().to_string());
+        } else if let Some(quote) = trimmed.strip_prefix('>') {
+            lines.push(format!(
+                "{} {}",
+                "│".dimmed(),
+                render_inline(quote.strip_prefix(' ').unwrap_or(quote)).italic()
+            ));
+        } else if let Some((indent, marker, text)) = list_item(line) {
+            lines.push(format!(
+                "{}{} {}",
+                indent,
+                marker.cyan(),
+                render_inline(text)
+            ));
+        } else if trimmed.starts_with('|') {
+            // table rows are shown as they are, separator rows are dimmed:
+            if trimmed.chars().all(|c| "|-: ".contains(c)) {
+                lines.push(line.dimmed().to_string());
+            } else {
+                lines.push(render_inline(line));
+            }
+        } else {
+            lines.push(render_inline(line));
+        }
+    }
+
+    lines.join("\n")
+}
+
+/**
+ * Renders the header of an issue or merge request: the title, a summary line, the given attributes
+ * and the URL. Attributes with empty values are skipped.
+ */
+pub fn render_header(
+    title: &str,
+    summary: &[String],
+    attributes: &[(&str, String)],
+    web_url: &str,
+) -> String {
+    let mut lines = vec![title.bold().to_string(), summary.join(" · ")];
+    for (name, value) in attributes.iter().filter(|(_, value)| !value.is_empty()) {
+        lines.push(format!("{} {}", format!("{}:", name).dimmed(), value));
+    }
+    lines.push(web_url.dimmed().to_string());
+
+    lines.join("\n")
+}
+
+/// returns the given state, e.g. 'opened', coloured by its meaning
+pub fn render_state(state: &str) -> String {
+    match state {
+        "opened" => state.green().to_string(),
+        "merged" => state.blue().to_string(),
+        _ => state.red().to_string(),
+    }
+}
+
+/// returns the given lines indented by the given prefix
+fn indent(text: &str, prefix: &str) -> String {
+    text.lines()
+        .map(|line| format!("{}{}", prefix, line))
+        .collect::<Vec<String>>()
+        .join("\n")
+}
+
+/**
+ * Renders the given comment threads, replies are indented below the first comment of their thread.
+ */
+pub fn render_discussions(discussions: &[Discussion]) -> String {
+    let mut out = format!(
+        "{}\n",
+        format!(
+            "── Discussion ({}) {}",
+            discussions.len(),
+            "─".repeat(RULE_WIDTH.saturating_sub(17))
+        )
+        .dimmed()
+    );
+
+    for discussion in discussions {
+        for (i, note) in discussion.notes.iter().enumerate() {
+            let prefix = if i == 0 { "" } else { "    " };
+            let mut header = vec![note.author.bold().to_string()];
+            header.push(format_time(&note.created_at).dimmed().to_string());
+            if let Some(position) = &note.position {
+                header.push(position.cyan().to_string());
+            }
+            if i == 0 {
+                match discussion.resolved {
+                    Some(true) => header.push("resolved".green().to_string()),
+                    Some(false) => header.push("unresolved".yellow().to_string()),
+                    None => {}
+                }
+            }
+
+            out += &format!("\n{}{}\n", prefix, header.join(" · "));
+            out += &indent(&render_markdown(&note.body), &format!("{}  ", prefix));
+            out += "\n";
+        }
+    }
+
+    out
+}
@@ -0,0 +1,664 @@
+use clap::ArgMatches;
+use colored::Colorize;
+use eyre::Report;
+use std::collections::HashMap;
+use std::fmt;
+use std::path::PathBuf;
+use std::process::{Command, Stdio};
+
+use crate::{
+    cli::{
+        ARG_COLUMNS, ARG_CONVERTER, ARG_EDITOR, ARG_FORGE, ARG_FORMAT, ARG_HOST, ARG_PROJECT_PATH,
+        ARG_REMOTE, ARG_TOKEN, ARG_UPSTREAM, ARG_USE_ORGMODE,
+    },
+    commands::list_issues::DEFAULT_COLUMNS,
+    config::{load_config_layers, ConfigLayer, ConfigScope, FormatConfig, Profile},
+    convert::{OrgConverter, MARKDOWN, ORG},
+    forges::ForgeKind,
+    remote::{get_remote_project, RemoteProject},
+    token::TokenSource,
+};
+
+/// name of the git remote used if none is configured
+const DEFAULT_REMOTE: &str = "origin";
+
+/**
+ * Describes where the effective value of a setting came from.
+ */
+#[derive(Debug, Clone)]
+pub enum Source {
+    /// passed as CLI argument, e.g. '--host'
+    Cli(&'static str),
+    /// read from the environment variable with the given name
+    Env(String),
+    /// read from the top level of the config file at the given path
+    ConfigFile(PathBuf),
+    /// read from the profile of the given host in the config file at the given path
+    Profile(PathBuf, String),
+    /// derived from the URL of the git remote with the given name
+    GitRemote(String),
+    /// the editor configured for git, e.g. as 'core.editor'
+    GitEditor,
+    /// built-in default value
+    Default,
+}
+
+impl fmt::Display for Source {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Source::Cli(flag) => write!(f, "CLI argument {}", flag),
+            Source::Env(name) => write!(f, "env {}", name),
+            Source::ConfigFile(path) => write!(f, "{}", path.display()),
+            Source::Profile(path, host) => write!(f, "{} [hosts.\"{}\"]", path.display(), host),
+            Source::GitRemote(name) => write!(f, "git remote '{}'", name),
+            Source::GitEditor => write!(f, "git var GIT_EDITOR"),
+            Source::Default => write!(f, "default"),
+        }
+    }
+}
+
+/**
+ * The effective value of a setting together with its source.
+ */
+#[derive(Debug, Clone)]
+pub struct Setting<T> {
+    pub value: T,
+    pub source: Source,
+}
+
+impl<T> Setting<T> {
+    fn new(value: T, source: Source) -> Setting<T> {
+        Setting { value, source }
+    }
+}
+
+/**
+ * All settings resolved from CLI arguments, environment variables, config files and the git remote.
+ *
+ * Settings are resolved in the following order, the first one found wins:
+ * 1. CLI arguments
+ * 2. environment variables
+ * 3. the repository-local `.forge.toml` (host profile first, then top level entries)
+ * 4. the git remote (only if no project path is set by one of the above)
+ * 5. the user's `~/.config/forge/config.toml` (host profile first, then top level entries)
+ * 6. built-in defaults
+ *
+ * The host, token sources, editor and formats are never read from the repository-local `.forge.toml`,
+ * as a cloned repository must not be able to run commands or obtain the token.
+ */
+#[derive(Debug)]
+pub struct Settings {
+    pub remote: Setting<String>,
+    pub project_path: Option<Setting<String>>,
+    /// path of the upstream project if the project is a fork
+    pub upstream: Option<Setting<String>>,
+    pub host: Setting<String>,
+    pub forge: Setting<ForgeKind>,
+    pub token: Option<Setting<TokenSource>>,
+    pub editor: Option<Setting<String>>,
+    /// the name of the format of the editor buffer, e.g. 'org'
+    pub format: Setting<String>,
+    pub converter: Setting<OrgConverter>,
+    /// the formats configured in all config files by name
+    pub formats: HashMap<String, FormatConfig>,
+    pub labels: Setting<Vec<String>>,
+    pub issue_columns: Setting<Vec<String>>,
+    /// the reason why the project path could not be derived from the git remote, if any
+    pub remote_error: Option<Report>,
+}
+
+/**
+ * Returns the value of the given CLI argument, distinguishing between values passed on the
+ * command line and values read from the argument's environment variable.
+ */
+fn from_args(
+    matches: &ArgMatches,
+    name: &str,
+    flag: &'static str,
+    env_name: &str,
+) -> Option<Setting<String>> {
+    matches.value_of(name).map(|value| {
+        let source = if matches.occurrences_of(name) > 0 {
+            Source::Cli(flag)
+        } else {
+            Source::Env(env_name.to_string())
+        };
+        Setting::new(value.to_string(), source)
+    })
+}
+
+/**
+ * Returns the first top level value of the given config scope found by `get`.
+ */
+fn from_config<T>(
+    layers: &[ConfigLayer],
+    scope: ConfigScope,
+    get: impl Fn(&ConfigLayer) -> Option<T>,
+) -> Option<Setting<T>> {
+    layers
+        .iter()
+        .filter(|layer| layer.scope == scope)
+        .find_map(|layer| {
+            get(layer).map(|v| Setting::new(v, Source::ConfigFile(layer.path.clone())))
+        })
+}
+
+/**
+ * Returns the first profile value of the given config scope found by `get`.
+ * The profile of the given host name takes precedence over the top level entries of the same file.
+ */
+fn from_profiles<T>(
+    layers: &[ConfigLayer],
+    scope: ConfigScope,
+    host_name: &str,
+    get: impl Fn(&Profile) -> Option<T>,
+) -> Option<Setting<T>> {
+    layers
+        .iter()
+        .filter(|layer| layer.scope == scope)
+        .find_map(|layer| {
+            let from_profile = layer.file.hosts.get(host_name).and_then(|profile| {
+                get(profile).map(|v| {
+                    Setting::new(
+                        v,
+                        Source::Profile(layer.path.clone(), host_name.to_string()),
+                    )
+                })
+            });
+            from_profile.or_else(|| {
+                get(&layer.file.defaults)
+                    .map(|v| Setting::new(v, Source::ConfigFile(layer.path.clone())))
+            })
+        })
+}
+
+/**
+ * Returns the first profile value found by `get`, searching the repository-local config file first.
+ */
+fn from_any_profile<T>(
+    layers: &[ConfigLayer],
+    host_name: &str,
+    get: impl Fn(&Profile) -> Option<T>,
+) -> Option<Setting<T>> {
+    from_profiles(layers, ConfigScope::Repository, host_name, &get)
+        .or_else(|| from_profiles(layers, ConfigScope::User, host_name, &get))
+}
+
+/**
+ * Returns the token source configured in the given profile, if any.
+ * A token command takes precedence over a token environment variable and other token sources.
+ */
+fn profile_token_source(profile: &Profile) -> Option<Result<TokenSource, Report>> {
+    if let Some(cmd) = &profile.token_command {
+        Some(Ok(TokenSource::Command(cmd.clone())))
+    } else if let Some(name) = &profile.token_env {
+        Some(Ok(TokenSource::Env(name.clone())))
+    } else {
+        profile
+            .token_source
+            .as_ref()
+            .map(|name| TokenSource::from_name(name))
+    }
+}
+
+/// returns the value of the given environment variable, unless it is empty
+fn from_env(name: &str) -> Option<Setting<String>> {
+    std::env::var(name)
+        .ok()
+        .filter(|value| !value.trim().is_empty())
+        .map(|value| Setting::new(value, Source::Env(name.to_string())))
+}
+
+/**
+ * Returns the editor git would use, based on GIT_EDITOR, 'core.editor', VISUAL and EDITOR,
+ * falling back to git's default editor.
+ */
+fn git_editor() -> Option<Setting<String>> {
+    let output = Command::new("git")
+        .args(["var", "GIT_EDITOR"])
+        .stderr(Stdio::null())
+        .output()
+        .ok()
+        .filter(|output| output.status.success())?;
+    let editor = String::from_utf8_lossy(&output.stdout).trim().to_string();
+
+    Some(Setting::new(editor, Source::GitEditor)).filter(|editor| !editor.value.is_empty())
+}
+
+/// returns the host name (including the port, if any) of the given base URL, e.g. 'gitlab.example.com'
+fn host_name(host: &str) -> &str {
+    host.split_once("://")
+        .map(|(_, rest)| rest)
+        .unwrap_or(host)
+        .trim_end_matches('/')
+}
+
+impl Settings {
+    /**
+     * Resolves all settings based on the given CLI arguments. Arguments of the chosen (possibly nested)
+     * sub-command (e.g. the editor) are taken into account as well.
+     */
+    pub fn resolve(cli_args: &ArgMatches) -> Result<Settings, Report> {
+        let layers = load_config_layers()?;
+        let mut cmd_args = cli_args.subcommand().1;
+        while let Some(nested_args) = cmd_args.and_then(|args| args.subcommand().1) {
+            cmd_args = Some(nested_args);
+        }
+
+        let remote = from_args(cli_args, ARG_REMOTE, "--remote", "FORGE_CLI_REMOTE")
+            .or_else(|| from_config(&layers, ConfigScope::Repository, |l| l.file.remote.clone()))
+            .or_else(|| from_config(&layers, ConfigScope::User, |l| l.file.remote.clone()))
+            .unwrap_or_else(|| Setting::new(String::from(DEFAULT_REMOTE), Source::Default));
+
+        let explicit_project_path =
+            from_args(cli_args, ARG_PROJECT_PATH, "-p", "FORGE_CLI_PROJECT_PATH").or_else(|| {
+                from_config(&layers, ConfigScope::Repository, |l| {
+                    l.file.project_path.clone()
+                })
+            });
+
+        // the git remote is only consulted if the project path is not set explicitly:
+        let (project_path, remote_project, remote_error) = match explicit_project_path {
+            Some(project_path) => (Some(project_path), None, None),
+            None => match get_remote_project(&remote.value) {
+                Ok(remote_project) => (
+                    Some(Setting::new(
+                        remote_project.project_path.clone(),
+                        Source::GitRemote(remote.value.clone()),
+                    )),
+                    Some(remote_project),
+                    None,
+                ),
+                Err(err) => (None, None, Some(err)),
+            },
+        };
+        // like the project path, the upstream project is specific to the repository:
+        let upstream = from_args(cli_args, ARG_UPSTREAM, "--upstream", "FORGE_CLI_UPSTREAM")
+            .or_else(|| {
+                from_config(&layers, ConfigScope::Repository, |l| {
+                    l.file.upstream.clone()
+                })
+            });
+
+        let from_remote = |get: fn(&RemoteProject) -> Option<String>| {
+            remote_project.as_ref().and_then(|remote_project| {
+                get(remote_project)
+                    .map(|v| Setting::new(v, Source::GitRemote(remote.value.clone())))
+            })
+        };
+
+        // the host may only fall back to the default host of the forge, which is resolved below.
+        // The token is sent to the host, so it cannot be set by the repository-local config file:
+        let host = from_args(cli_args, ARG_HOST, "--host", "FORGE_CLI_HOST")
+            .or_else(|| from_remote(|r| Some(r.host.clone())))
+            .or_else(|| from_config(&layers, ConfigScope::User, |l| l.file.host.clone()));
+        let profile_host = host
+            .as_ref()
+            .map(|host| host_name(&host.value).to_string())
+            .unwrap_or_default();
+
+        let forge = from_args(cli_args, ARG_FORGE, "--forge", "FORGE_CLI_FORGE")
+            .or_else(|| {
+                from_profiles(&layers, ConfigScope::Repository, &profile_host, |p| {
+                    p.forge.clone()
+                })
+            })
+            .or_else(|| from_remote(|r| r.forge.map(|forge| forge.name().to_string())))
+            .or_else(|| {
+                from_profiles(&layers, ConfigScope::User, &profile_host, |p| {
+                    p.forge.clone()
+                })
+            });
+        let forge = match forge {
+            Some(Setting { value, source }) => Setting::new(value.parse()?, source),
+            None => Setting::new(ForgeKind::Gitlab, Source::Default),
+        };
+
+        let host = host
+            .map(|Setting { value, source }| {
+                Setting::new(value.trim_end_matches('/').to_string(), source)
+            })
+            .unwrap_or_else(|| {
+                Setting::new(String::from(forge.value.default_host()), Source::Default)
+            });
+        let host_name = host_name(&host.value).to_string();
+
+        let token = match from_args(cli_args, ARG_TOKEN, "-t", "FORGE_CLI_TOKEN") {
+            Some(Setting { value, source }) => {
+                Some(Setting::new(TokenSource::Plain(value), source))
+            }
+            None => from_profiles(&layers, ConfigScope::User, &host_name, profile_token_source)
+                .map(|Setting { value, source }| value.map(|value| Setting::new(value, source)))
+                .transpose()?,
+        };
+
+        let editor = cmd_args
+            .and_then(|cmd_args| from_args(cmd_args, ARG_EDITOR, "-e", "FORGE_CLI_EDITOR"))
+            .or_else(|| from_profiles(&layers, ConfigScope::User, &host_name, |p| p.editor.clone()))
+            .or_else(|| from_env("VISUAL"))
+            .or_else(|| from_env("EDITOR"))
+            .or_else(git_editor);
+
+        // the environment variable is not read by clap, so it does not conflict with '-o',
+        // and 'org_mode = true' is a shorthand for 'format = "org"':
+        let format = match cmd_args {
+            Some(cmd_args) if cmd_args.is_present(ARG_USE_ORGMODE) => {
+                Some(Setting::new(String::from(ORG), Source::Cli("-o")))
+            }
+            Some(cmd_args) => cmd_args
+                .value_of(ARG_FORMAT)
+                .map(|format| Setting::new(format.to_string(), Source::Cli("--format"))),
+            None => None,
+        }
+        .or_else(|| {
+            std::env::var("FORGE_CLI_FORMAT")
+                .ok()
+                .map(|format| Setting::new(format, Source::Env(String::from("FORGE_CLI_FORMAT"))))
+        })
+        .or_else(|| {
+            from_any_profile(&layers, &host_name, |p| {
+                p.format.clone().or_else(|| {
+                    p.org_mode
+                        .map(|org_mode| String::from(if org_mode { ORG } else { MARKDOWN }))
+                })
+            })
+        })
+        .unwrap_or_else(|| Setting::new(String::from(MARKDOWN), Source::Default));
+
+        // formats run commands, so they are only read from the user's config file:
+        let formats: HashMap<String, FormatConfig> = layers
+            .iter()
+            .filter(|layer| layer.scope == ConfigScope::User)
+            .flat_map(|layer| layer.file.formats.clone())
+            .collect();
+
+        let converter = from_args(
+            cli_args,
+            ARG_CONVERTER,
+            "--converter",
+            "FORGE_CLI_CONVERTER",
+        )
+        .or_else(|| from_any_profile(&layers, &host_name, |p| p.converter.clone()));
+        let converter = match converter {
+            Some(Setting { value, source }) => Setting::new(value.parse()?, source),
+            None => Setting::new(OrgConverter::Builtin, Source::Default),
+        };
+
+        let labels = from_any_profile(&layers, &host_name, |p| p.labels.clone())
+            .unwrap_or_else(|| Setting::new(vec![], Source::Default));
+
+        // the CLI argument and environment variable contain comma separated column names:
+        let issue_columns = cmd_args
+            .and_then(|cmd_args| {
+                from_args(
+                    cmd_args,
+                    ARG_COLUMNS,
+                    "--columns",
+                    "FORGE_CLI_ISSUE_COLUMNS",
+                )
+            })
+            .map(|Setting { value, source }| {
+                let columns = value
+                    .split(',')
+                    .map(|column| column.trim().to_string())
+                    .filter(|column| !column.is_empty())
+                    .collect();
+                Setting::new(columns, source)
+            })
+            .or_else(|| from_any_profile(&layers, &host_name, |p| p.issue_columns.clone()))
+            .unwrap_or_else(|| {
+                Setting::new(
+                    DEFAULT_COLUMNS
+                        .iter()
+                        .map(|column| column.to_string())
+                        .collect(),
+                    Source::Default,
+                )
+            });
+
+        Ok(Settings {
+            remote,
+            project_path,
+            upstream,
+            host,
+            forge,
+            token,
+            editor,
+            format,
+            converter,
+            formats,
+            labels,
+            issue_columns,
+            remote_error,
+        })
+    }
+
+    /**
+     * Returns the name, effective value and source of every setting. Settings which are not set
+     * have no value and the reason why instead of a source. The token is masked.
+     */
+    fn entries(&self) -> Vec<(&'static str, Option<String>, String)> {
+        fn set<T>(
+            name: &'static str,
+            value: String,
+            setting: &Setting<T>,
+        ) -> (&'static str, Option<String>, String) {
+            (name, Some(value), setting.source.to_string())
+        }
+        fn unset(name: &'static str, reason: &str) -> (&'static str, Option<String>, String) {
+            (name, None, reason.to_string())
+        }
+
+        vec![
+            set("forge", self.forge.value.name().to_string(), &self.forge),
+            set("host", self.host.value.clone(), &self.host),
+            match (&self.project_path, &self.remote_error) {
+                (Some(path), _) => set("project-path", path.value.clone(), path),
+                (None, Some(err)) => unset("project-path", &err.to_string()),
+                (None, None) => unset("project-path", "not set"),
+            },
+            match &self.upstream {
+                Some(upstream) => set("upstream", upstream.value.clone(), upstream),
+                None => unset("upstream", "not set"),
+            },
+            set("remote", self.remote.value.clone(), &self.remote),
+            match &self.token {
+                Some(token) => set("token", token.value.to_string(), token),
+                None => unset("token", "not set"),
+            },
+            match &self.editor {
+                Some(editor) => set("editor", editor.value.clone(), editor),
+                None => unset("editor", "not set"),
+            },
+            set("format", self.format.value.clone(), &self.format),
+            set(
+                "converter",
+                self.converter.value.name().to_string(),
+                &self.converter,
+            ),
+            set("labels", self.labels.value.join(", "), &self.labels),
+            set(
+                "issue-columns",
+                self.issue_columns.value.join(", "),
+                &self.issue_columns,
+            ),
+        ]
+    }
+
+    /**
+     * Returns a human readable overview of all effective settings and their sources.
+     * The token is masked.
+     */
+    pub fn describe(&self) -> String {
+        self.entries()
+            .into_iter()
+            .map(|(name, value, source)| {
+                format!(
+                    "{:<14}{:<40} {}\n",
+                    name,
+                    value.unwrap_or_else(|| String::from("-")),
+                    format!("({})", source).dimmed()
+                )
+            })
+            .collect()
+    }
+
+    /**
+     * Returns the settings as JSON object, e.g. `{"host": {"value": "...", "source": "default"}}`.
+     * The value of settings which are not set is `null`, their source is the reason why.
+     */
+    pub fn describe_json(&self) -> serde_json::Value {
+        self.entries()
+            .into_iter()
+            .map(|(name, value, source)| {
+                (
+                    name.to_string(),
+                    serde_json::json!({ "value": value, "source": source }),
+                )
+            })
+            .collect::<serde_json::Map<_, _>>()
+            .into()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn layer(scope: ConfigScope, path: &str, content: &str) -> ConfigLayer {
+        ConfigLayer {
+            scope,
+            path: PathBuf::from(path),
+            file: toml::from_str(content).unwrap(),
+        }
+    }
+
+    fn layers() -> Vec<ConfigLayer> {
+        vec![
+            layer(
+                ConfigScope::Repository,
+                "repo.toml",
+                "project_path = 'group/project'\norg_mode = true\n\
+                 [hosts.'gitlab.example.com']\nlabels = ['repo-host']\n",
+            ),
+            layer(
+                ConfigScope::User,
+                "user.toml",
+                "project_path = 'other/project'\nlabels = ['user']\nforge = 'gitea'\n\
+                 [hosts.'gitlab.example.com']\nforge = 'gitlab'\norg_mode = false\n",
+            ),
+        ]
+    }
+
+    fn describe<T: fmt::Debug>(setting: Option<Setting<T>>) -> Option<String> {
+        setting.map(|setting| format!("{:?} from {}", setting.value, setting.source))
+    }
+
+    #[test]
+    fn reads_top_level_entries_of_the_given_scope() {
+        let layers = layers();
+        let project_path = |layer: &ConfigLayer| layer.file.project_path.clone();
+        let upstream = |layer: &ConfigLayer| layer.file.upstream.clone();
+
+        assert_eq!(
+            describe(from_config(&layers, ConfigScope::Repository, project_path)),
+            Some(String::from("\"group/project\" from repo.toml"))
+        );
+        assert_eq!(
+            describe(from_config(&layers, ConfigScope::User, project_path)),
+            Some(String::from("\"other/project\" from user.toml"))
+        );
+        assert_eq!(
+            describe(from_config(&layers, ConfigScope::User, upstream)),
+            None
+        );
+    }
+
+    #[test]
+    fn prefers_host_profiles_within_a_file() {
+        let layers = layers();
+        assert_eq!(
+            describe(from_profiles(
+                &layers,
+                ConfigScope::User,
+                "gitlab.example.com",
+                |profile| profile.forge.clone()
+            )),
+            Some(String::from(
+                "\"gitlab\" from user.toml [hosts.\"gitlab.example.com\"]"
+            ))
+        );
+        assert_eq!(
+            describe(from_profiles(
+                &layers,
+                ConfigScope::User,
+                "codeberg.org",
+                |profile| profile.forge.clone()
+            )),
+            Some(String::from("\"gitea\" from user.toml"))
+        );
+    }
+
+    #[test]
+    fn prefers_repository_config_over_user_config() {
+        let layers = layers();
+        assert_eq!(
+            describe(from_any_profile(&layers, "gitlab.example.com", |profile| {
+                profile.org_mode
+            })),
+            Some(String::from("true from repo.toml"))
+        );
+        assert_eq!(
+            describe(from_any_profile(&layers, "gitlab.example.com", |profile| {
+                profile.labels.clone()
+            })),
+            Some(String::from(
+                "[\"repo-host\"] from repo.toml [hosts.\"gitlab.example.com\"]"
+            ))
+        );
+        assert_eq!(
+            describe(from_any_profile(&layers, "codeberg.org", |profile| {
+                profile.labels.clone()
+            })),
+            Some(String::from("[\"user\"] from user.toml"))
+        );
+    }
+
+    #[test]
+    fn prefers_token_commands_over_other_token_sources() {
+        let profile: Profile = toml::from_str(
+            "token_command = 'pass show gitlab'\ntoken_env = 'TOKEN'\ntoken_source = 'netrc'\n",
+        )
+        .unwrap();
+        assert_eq!(
+            profile_token_source(&profile).unwrap().unwrap(),
+            TokenSource::Command(String::from("pass show gitlab"))
+        );
+
+        let profile: Profile =
+            toml::from_str("token_env = 'TOKEN'\ntoken_source = 'netrc'\n").unwrap();
+        assert_eq!(
+            profile_token_source(&profile).unwrap().unwrap(),
+            TokenSource::Env(String::from("TOKEN"))
+        );
+
+        let profile: Profile = toml::from_str("token_source = 'netrc'\n").unwrap();
+        assert_eq!(
+            profile_token_source(&profile).unwrap().unwrap(),
+            TokenSource::Netrc
+        );
+
+        let profile: Profile = toml::from_str("token_source = 'keyring'\n").unwrap();
+        assert!(profile_token_source(&profile).unwrap().is_err());
+        assert!(profile_token_source(&Profile::default()).is_none());
+    }
+
+    #[test]
+    fn extracts_host_names() {
+        assert_eq!(
+            host_name("https://gitlab.example.com:8443/"),
+            "gitlab.example.com:8443"
+        );
+        assert_eq!(host_name("gitlab.example.com"), "gitlab.example.com");
+    }
+}
@@ -0,0 +1,88 @@
+use colored::{ColoredString, Colorize};
+
+/**
+ * A cell of a table printed by `print_table`, coloured by the given style when printed.
+ */
+pub struct Cell {
+    pub text: String,
+    pub style: fn(String) -> ColoredString,
+}
+
+impl Cell {
+    /// returns a new cell printed without colours
+    pub fn new(text: impl Into<String>) -> Cell {
+        Cell::styled(text, |text| text.normal())
+    }
+
+    /// returns a new cell coloured by the given style, e.g. `|text| text.green()`
+    pub fn styled(text: impl Into<String>, style: fn(String) -> ColoredString) -> Cell {
+        Cell {
+            text: text.into(),
+            style,
+        }
+    }
+}
+
+/// returns the given text cut to the given number of characters, marking cut texts with an ellipsis
+pub fn truncate(text: &str, width: usize) -> String {
+    if text.chars().count() <= width {
+        text.to_string()
+    } else {
+        let cut: String = text.chars().take(width.saturating_sub(1)).collect();
+        format!("{}…", cut)
+    }
+}
+
+/// returns the given text padded with spaces to the given number of characters
+fn pad(text: &str, width: usize) -> String {
+    format!(
+        "{}{}",
+        text,
+        " ".repeat(width.saturating_sub(text.chars().count()))
+    )
+}
+
+/// formats an ISO 8601 timestamp as 'YYYY-MM-DD HH:MM'
+pub fn format_time(time: &str) -> String {
+    time.replacen('T', " ", 1).chars().take(16).collect()
+}
+
+/**
+ * Prints the given rows as table below the given bold headers, one row per line.
+ * Columns are aligned by padding the cells with spaces, the colours are applied after padding.
+ */
+pub fn print_table(headers: &[&str], rows: &[Vec<Cell>]) {
+    let mut widths: Vec<usize> = headers
+        .iter()
+        .map(|header| header.chars().count())
+        .collect();
+    for row in rows {
+        for (width, cell) in widths.iter_mut().zip(row) {
+            *width = (*width).max(cell.text.chars().count());
+        }
+    }
+    // the last column is not padded to avoid trailing whitespace:
+    if let Some(width) = widths.last_mut() {
+        *width = 0;
+    }
+    let print_line = |cells: Vec<ColoredString>| {
+        let cells: Vec<String> = cells.iter().map(ColoredString::to_string).collect();
+        println!("{}", cells.join("  "));
+    };
+
+    print_line(
+        headers
+            .iter()
+            .zip(&widths)
+            .map(|(header, width)| pad(header, *width).bold())
+            .collect(),
+    );
+    for row in rows {
+        print_line(
+            row.iter()
+                .zip(&widths)
+                .map(|(cell, width)| (cell.style)(pad(&cell.text, *width)))
+                .collect(),
+        );
+    }
+}
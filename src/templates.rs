@@ -0,0 +1,103 @@
+use eyre::{eyre, Context, Report};
+use std::fs;
+use std::path::PathBuf;
+
+use crate::{config::repo_root, prompt::choose};
+
+/**
+ * The kinds of description templates, stored in different directories of the repository.
+ */
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TemplateKind {
+    MergeRequest,
+    Issue,
+}
+
+impl TemplateKind {
+    /// returns the directory of the templates, relative to the repository root
+    fn directory(&self) -> &'static str {
+        match self {
+            TemplateKind::MergeRequest => ".gitlab/merge_request_templates",
+            TemplateKind::Issue => ".gitlab/issue_templates",
+        }
+    }
+}
+
+/**
+ * A markdown template stored in the repository, e.g. '.gitlab/issue_templates/Bug.md'.
+ */
+#[derive(Debug)]
+pub struct Template {
+    /// name of the template, which is the file name without extension, e.g. 'Bug'
+    pub name: String,
+    pub path: PathBuf,
+}
+
+impl Template {
+    /// reads the content of the template
+    pub fn read(&self) -> Result<String, Report> {
+        fs::read_to_string(&self.path)
+            .wrap_err_with(|| format!("Could not read template {:?}", self.path))
+    }
+}
+
+/**
+ * Returns all templates of the given kind in the current repository, sorted by name.
+ */
+pub fn list_templates(kind: TemplateKind) -> Result<Vec<Template>, Report> {
+    let dir = match repo_root() {
+        Some(root) => root.join(kind.directory()),
+        None => return Ok(vec![]),
+    };
+    if !dir.is_dir() {
+        return Ok(vec![]);
+    }
+
+    let mut templates = vec![];
+    for entry in fs::read_dir(&dir).wrap_err_with(|| format!("Could not read {:?}", dir))? {
+        let path = entry?.path();
+        if path.extension().is_some_and(|ext| ext == "md") {
+            let name = path
+                .file_stem()
+                .map(|stem| stem.to_string_lossy().to_string())
+                .unwrap_or_default();
+            templates.push(Template { name, path });
+        }
+    }
+    templates.sort_by(|a, b| a.name.cmp(&b.name));
+
+    Ok(templates)
+}
+
+/**
+ * Returns the content of the template to use:
+ * - the template with the given name, if any. Fails if there is no such template.
+ * - the only template, if there is exactly one.
+ * - the template chosen by the user, if there are several ones.
+ */
+pub fn select_template(kind: TemplateKind, name: Option<&str>) -> Result<Option<String>, Report> {
+    let templates = list_templates(kind)?;
+
+    let template = match name {
+        Some(name) => Some(
+            templates
+                .iter()
+                .find(|template| template.name == name)
+                .ok_or_else(|| eyre!("Template '{}' not found in {}", name, kind.directory()))?,
+        ),
+        None => match templates.len() {
+            0 => None,
+            1 => Some(&templates[0]),
+            _ => {
+                let mut options: Vec<String> = vec![String::from("(no template)")];
+                options.extend(templates.iter().map(|template| template.name.clone()));
+                match choose("Choose a template:", &options)? {
+                    0 => None,
+                    index => Some(&templates[index - 1]),
+                }
+            }
+        },
+    };
+
+    template.map(|template| template.read()).transpose()
+}
@@ -0,0 +1,277 @@
+use eyre::{eyre, Context, Report};
+use std::fmt;
+use std::fs;
+use std::io::Write;
+use std::path::PathBuf;
+use std::process::{Command, Stdio};
+
+/// value of the `token_source` config entry selecting the git credential helper
+pub const TOKEN_SOURCE_GIT_CREDENTIAL: &str = "git-credential";
+/// value of the `token_source` config entry selecting the netrc file
+pub const TOKEN_SOURCE_NETRC: &str = "netrc";
+
+/**
+ * The ways the authentication token can be obtained.
+ */
+#[derive(Debug, Clone, PartialEq)]
+pub enum TokenSource {
+    /// the token itself, e.g. passed as CLI argument
+    Plain(String),
+    /// the name of an environment variable containing the token
+    Env(String),
+    /// a shell command printing the token, e.g. 'pass show gitlab'
+    Command(String),
+    /// the password returned by `git credential fill` for the host
+    GitCredential,
+    /// the password of the host's entry in `~/.netrc`
+    Netrc,
+}
+
+impl fmt::Display for TokenSource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TokenSource::Plain(_) => write!(f, "********"),
+            TokenSource::Env(name) => write!(f, "env {}", name),
+            TokenSource::Command(cmd) => write!(f, "command '{}'", cmd),
+            TokenSource::GitCredential => write!(f, "git credential fill"),
+            TokenSource::Netrc => write!(f, "~/.netrc"),
+        }
+    }
+}
+
+impl TokenSource {
+    /**
+     * Parses the value of the `token_source` config entry.
+     */
+    pub fn from_name(name: &str) -> Result<TokenSource, Report> {
+        match name {
+            TOKEN_SOURCE_GIT_CREDENTIAL => Ok(TokenSource::GitCredential),
+            TOKEN_SOURCE_NETRC => Ok(TokenSource::Netrc),
+            _ => Err(eyre!(
+                "Invalid token source '{}', expected '{}' or '{}'",
+                name,
+                TOKEN_SOURCE_GIT_CREDENTIAL,
+                TOKEN_SOURCE_NETRC
+            )),
+        }
+    }
+
+    /**
+     * Returns the token for the given host, e.g. 'https://gitlab.example.com'.
+     */
+    pub fn resolve(&self, host: &str) -> Result<String, Report> {
+        let token = match self {
+            TokenSource::Plain(token) => token.clone(),
+            TokenSource::Env(name) => std::env::var(name)
+                .wrap_err_with(|| format!("Missing environment variable {}", name))?,
+            TokenSource::Command(cmd) => run_token_command(cmd)?,
+            TokenSource::GitCredential => git_credential_fill(host)?,
+            TokenSource::Netrc => netrc_password(host)?,
+        };
+
+        let token = token.trim().to_string();
+        if token.is_empty() {
+            Err(eyre!("The token obtained from {} is empty", self))
+        } else {
+            Ok(token)
+        }
+    }
+}
+
+/**
+ * Splits the given base URL into its protocol and host name, e.g. ('https', 'gitlab.example.com:8443').
+ */
+fn split_host(host: &str) -> (&str, &str) {
+    host.split_once("://").unwrap_or(("https", host))
+}
+
+/**
+ * Runs the given command in a shell and returns its output.
+ */
+fn run_token_command(cmd: &str) -> Result<String, Report> {
+    let output = Command::new("sh")
+        .args(["-c", cmd])
+        .stderr(Stdio::inherit())
+        .output()
+        .wrap_err_with(|| format!("Failed to run token command '{}'", cmd))?;
+
+    if !output.status.success() {
+        return Err(eyre!(
+            "Token command '{}' failed with {}",
+            cmd,
+            output.status
+        ));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).to_string())
+}
+
+/**
+ * Asks the configured git credential helpers for the password of the given host.
+ * Git is prevented from prompting for credentials on the terminal.
+ */
+fn git_credential_fill(host: &str) -> Result<String, Report> {
+    let (protocol, host_name) = split_host(host);
+
+    let mut child = Command::new("git")
+        .args(["credential", "fill"])
+        .env("GIT_TERMINAL_PROMPT", "0")
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .spawn()
+        .wrap_err("Failed to run git credential fill")?;
+    write!(
+        child.stdin.take().unwrap(),
+        "protocol={}\nhost={}\n\n",
+        protocol,
+        host_name
+    )?;
+
+    let output = child
+        .wait_with_output()
+        .wrap_err("Failed to run git credential fill")?;
+    if !output.status.success() {
+        return Err(eyre!(
+            "git credential fill found no credentials for {}",
+            host
+        ));
+    }
+
+    String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .find_map(|line| line.strip_prefix("password="))
+        .map(String::from)
+        .ok_or_else(|| eyre!("git credential fill returned no password for {}", host))
+}
+
+/// returns the path of the netrc file, which can be overwritten by the `NETRC` environment variable
+fn netrc_file_path() -> Option<PathBuf> {
+    std::env::var_os("NETRC")
+        .map(PathBuf::from)
+        .or_else(|| dirs::home_dir().map(|home| home.join(".netrc")))
+}
+
+/**
+ * Returns the password of the netrc entry matching the name of the given host,
+ * falling back to the `default` entry.
+ */
+fn netrc_password(host: &str) -> Result<String, Report> {
+    let path = netrc_file_path().ok_or_else(|| eyre!("Could not locate the netrc file"))?;
+    let content =
+        fs::read_to_string(&path).wrap_err_with(|| format!("Could not read {:?}", path))?;
+
+    let host_name = split_host(host).1.split(':').next().unwrap_or_default();
+    find_netrc_password(&content, host_name)
+        .map(String::from)
+        .ok_or_else(|| eyre!("No entry for {} found in {:?}", host_name, path))
+}
+
+/**
+ * Returns the password of the entry for the given host name in the given netrc file content,
+ * falling back to the `default` entry.
+ */
+fn find_netrc_password<'a>(content: &'a str, host_name: &str) -> Option<&'a str> {
+    let mut tokens = content.split_whitespace();
+    let mut current_machine: Option<&str> = None;
+    let mut default_password = None;
+
+    while let Some(token) = tokens.next() {
+        match token {
+            "machine" => current_machine = tokens.next(),
+            "default" => current_machine = Some(""),
+            "password" => {
+                let password = tokens.next();
+                match current_machine {
+                    Some(machine) if machine == host_name => return password,
+                    Some("") => default_password = password,
+                    _ => {}
+                }
+            }
+            // skip the values of all other keywords:
+            "login" | "account" => {
+                tokens.next();
+            }
+            _ => {}
+        }
+    }
+
+    default_password
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const NETRC: &str = "machine github.com login me password gh-secret\n\
+                         machine gitlab.example.com\n  login me\n  password gl-secret\n\
+                         default login anonymous password fallback\n";
+
+    #[test]
+    fn splits_hosts() {
+        assert_eq!(
+            split_host("https://gitlab.example.com:8443"),
+            ("https", "gitlab.example.com:8443")
+        );
+        assert_eq!(
+            split_host("gitlab.example.com"),
+            ("https", "gitlab.example.com")
+        );
+        assert_eq!(split_host("http://localhost"), ("http", "localhost"));
+    }
+
+    #[test]
+    fn finds_netrc_password_of_host() {
+        assert_eq!(find_netrc_password(NETRC, "github.com"), Some("gh-secret"));
+        assert_eq!(
+            find_netrc_password(NETRC, "gitlab.example.com"),
+            Some("gl-secret")
+        );
+    }
+
+    #[test]
+    fn falls_back_to_default_netrc_entry() {
+        assert_eq!(find_netrc_password(NETRC, "codeberg.org"), Some("fallback"));
+        assert_eq!(
+            find_netrc_password("machine github.com password secret", "codeberg.org"),
+            None
+        );
+    }
+
+    #[test]
+    fn does_not_mistake_logins_for_hosts() {
+        let netrc = "machine other.org login github.com password wrong";
+        assert_eq!(find_netrc_password(netrc, "github.com"), None);
+    }
+
+    #[test]
+    fn parses_token_sources() {
+        assert_eq!(TokenSource::from_name("netrc").unwrap(), TokenSource::Netrc);
+        assert_eq!(
+            TokenSource::from_name("git-credential").unwrap(),
+            TokenSource::GitCredential
+        );
+        assert!(TokenSource::from_name("keyring").is_err());
+    }
+
+    #[test]
+    fn resolves_trimmed_tokens() {
+        assert_eq!(
+            TokenSource::Plain(String::from(" secret\n"))
+                .resolve("https://gitlab.com")
+                .unwrap(),
+            "secret"
+        );
+        assert_eq!(
+            TokenSource::Command(String::from("echo secret"))
+                .resolve("https://gitlab.com")
+                .unwrap(),
+            "secret"
+        );
+        assert!(TokenSource::Command(String::from("true"))
+            .resolve("https://gitlab.com")
+            .is_err());
+        assert!(TokenSource::Command(String::from("exit 1"))
+            .resolve("https://gitlab.com")
+            .is_err());
+    }
+}